@@ -5,6 +5,7 @@ near deploy --accountId multichain.rubic-finance.near --wasmFile target/wasm32-u
         "relayer_id": "realhum.near",
         "transfer_token": "dac17f958d2ee523a2206206994597c13d831ec7.factory.bridge.near",
         "blockchain_router": "v2.ref-finance.near",
+        "transfer_token_decimals": 6,
         "num_of_this_blockchain": 9,
         "min_token_amount": "0",
         "max_token_amount": "9999999999999999999999",
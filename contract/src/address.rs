@@ -0,0 +1,197 @@
+use crate::interfaces::AddressFormat;
+
+use super::*;
+
+const EVM_ADDRESS_LEN: usize = 40;
+const SOLANA_ADDRESS_LEN: usize = 32;
+const TRON_ADDRESS_LEN: usize = 25;
+const TRON_ADDRESS_PREFIX: u8 = 0x41;
+/// Rubic contracts pass addresses left padded to 32 bytes,
+/// i.e. `0x` followed by 12 zero bytes and the address itself.
+const PADDED_ADDRESS_PREFIX: &str = "0x000000000000000000000000";
+
+impl AddressFormat {
+    /// Checks that `address` is valid for the blockchain with this format.
+    /// EVM addresses padded to 32 bytes are accepted as well.
+    pub fn is_valid_address(&self, address: &str) -> bool {
+        if self.is_valid_unpadded_address(address) {
+            return true;
+        }
+
+        match (self, address.strip_prefix(PADDED_ADDRESS_PREFIX)) {
+            (AddressFormat::Evm, Some(unpadded)) => {
+                is_valid_evm_address(&format!("0x{}", unpadded))
+            }
+            _ => false,
+        }
+    }
+
+    fn is_valid_unpadded_address(&self, address: &str) -> bool {
+        match self {
+            AddressFormat::Evm => is_valid_evm_address(address),
+            AddressFormat::Solana => is_valid_solana_address(address),
+            AddressFormat::Tron => is_valid_tron_address(address),
+            AddressFormat::Near => env::is_valid_account_id(address.as_bytes()),
+            AddressFormat::Other => !address.is_empty(),
+        }
+    }
+}
+
+/// `0x` prefixed 20 bytes hex. Mixed case addresses must match
+/// EIP-55 checksum, all lower or all upper case addresses are accepted
+/// without checksum.
+fn is_valid_evm_address(address: &str) -> bool {
+    let hex_part = match address.strip_prefix("0x") {
+        Some(hex_part) => hex_part,
+        None => return false,
+    };
+    if hex_part.len() != EVM_ADDRESS_LEN ||
+        !hex_part.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return false;
+    }
+
+    let is_lowercase = !hex_part.chars().any(|c| c.is_ascii_uppercase());
+    let is_uppercase = !hex_part.chars().any(|c| c.is_ascii_lowercase());
+    if is_lowercase || is_uppercase {
+        return true;
+    }
+
+    let hash = env::keccak256(hex_part.to_ascii_lowercase().as_bytes());
+    hex_part.chars().enumerate().all(|(i, c)| {
+        if !c.is_ascii_alphabetic() {
+            return true;
+        }
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+
+        c.is_ascii_uppercase() == (nibble >= 8)
+    })
+}
+
+/// Base58 encoded 32 bytes public key
+fn is_valid_solana_address(address: &str) -> bool {
+    match bs58::decode(address).into_vec() {
+        Ok(bytes) => bytes.len() == SOLANA_ADDRESS_LEN,
+        Err(_) => false,
+    }
+}
+
+/// Base58check encoded 21 bytes address starting with 0x41
+fn is_valid_tron_address(address: &str) -> bool {
+    let bytes = match bs58::decode(address).into_vec() {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+    if bytes.len() != TRON_ADDRESS_LEN || bytes[0] != TRON_ADDRESS_PREFIX {
+        return false;
+    }
+
+    let (payload, checksum) = bytes.split_at(TRON_ADDRESS_LEN - 4);
+    let hash = env::sha256(&env::sha256(payload));
+
+    hash[..4] == *checksum
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    use super::*;
+
+    const EVM_ADDRESS: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    const TRON_ADDRESS: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+
+    fn setup() {
+        testing_env!(VMContextBuilder::new().build());
+    }
+
+    #[test]
+    fn evm_address_with_checksum() {
+        setup();
+
+        assert!(AddressFormat::Evm.is_valid_address(EVM_ADDRESS));
+        assert!(AddressFormat::Evm.is_valid_address(
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"
+        ));
+        assert!(!AddressFormat::Evm.is_valid_address(
+            "0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        ));
+    }
+
+    #[test]
+    fn evm_address_in_single_case() {
+        setup();
+
+        assert!(AddressFormat::Evm.is_valid_address(
+            &EVM_ADDRESS.to_ascii_lowercase()
+        ));
+        assert!(AddressFormat::Evm.is_valid_address(
+            &format!("0x{}", EVM_ADDRESS[2..].to_ascii_uppercase())
+        ));
+    }
+
+    #[test]
+    fn evm_address_of_wrong_format() {
+        setup();
+
+        assert!(!AddressFormat::Evm.is_valid_address(&EVM_ADDRESS[2..]));
+        assert!(!AddressFormat::Evm.is_valid_address(&EVM_ADDRESS[..41]));
+        assert!(!AddressFormat::Evm.is_valid_address(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg"
+        ));
+    }
+
+    #[test]
+    fn tron_address() {
+        setup();
+
+        assert!(AddressFormat::Tron.is_valid_address(TRON_ADDRESS));
+        // wrong checksum
+        assert!(!AddressFormat::Tron.is_valid_address(
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u"
+        ));
+        // not base58
+        assert!(!AddressFormat::Tron.is_valid_address(
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj60"
+        ));
+        assert!(!AddressFormat::Tron.is_valid_address(EVM_ADDRESS));
+    }
+
+    #[test]
+    fn padded_address() {
+        setup();
+
+        assert!(AddressFormat::Evm.is_valid_address(
+            &format!("{}{}", PADDED_ADDRESS_PREFIX, &EVM_ADDRESS[2..])
+        ));
+        assert!(!AddressFormat::Evm.is_valid_address(
+            &format!("{}{}", PADDED_ADDRESS_PREFIX, "5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+        ));
+        assert!(!AddressFormat::Evm.is_valid_address(
+            &format!("0x{}{}", "00".repeat(11), &EVM_ADDRESS[2..])
+        ));
+        assert!(!AddressFormat::Tron.is_valid_address(
+            &format!("{}{}", PADDED_ADDRESS_PREFIX, TRON_ADDRESS)
+        ));
+        assert!(!AddressFormat::Solana.is_valid_address(
+            &format!("{}{}", PADDED_ADDRESS_PREFIX, "11111111111111111111111111111111")
+        ));
+    }
+
+    #[test]
+    fn solana_and_near_addresses() {
+        setup();
+
+        assert!(AddressFormat::Solana.is_valid_address(
+            "11111111111111111111111111111111"
+        ));
+        assert!(!AddressFormat::Solana.is_valid_address(TRON_ADDRESS));
+        assert!(AddressFormat::Near.is_valid_address("alice.near"));
+        assert!(!AddressFormat::Near.is_valid_address("Alice"));
+    }
+}
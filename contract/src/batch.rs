@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+use crate::interfaces::SwapFromBatchItem;
+
+use super::*;
+
+#[ext_contract(ext_self_batch)]
+pub trait AfterBatchTransfer {
+    fn callback_after_batch_transfer(
+        &mut self,
+        swap_from_events: Vec<SwapFromEvent>,
+        transit_amounts: Vec<U128>,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers tokens to end users of several swaps from other blockchains.
+    /// Every swap is checked independently. Rejected swaps are reported by
+    /// swapFromOtherBlockchainFailed event and do not revert the others.
+    /// Transfers of `transfer_token` without discount are settled together
+    /// by one callback, so a batch fits many more of them than of swaps.
+    /// * `items` - params and optional msg of every swap
+    #[payable]
+    pub fn swap_tokens_to_user_batch(&mut self, items: Vec<SwapFromBatchItem>) {
+        self.assert_contract_running();
+        self.assert_relayer();
+
+        let mut tx_hashes = HashSet::new();
+        let mut transfers: Vec<Promise> = Vec::new();
+        let mut swap_from_events = Vec::new();
+        let mut transit_amounts = Vec::new();
+
+        for item in items {
+            let original_tx_hash = item.params.original_tx_hash.clone();
+            let checked_message = self.check_swap_from(&item.params, &item.msg)
+                .and_then(|message| {
+                    // Swaps are marked as processed only in callbacks
+                    if !tx_hashes.insert(original_tx_hash.clone()) {
+                        return Err("Swap already processed");
+                    }
+                    let required_gas = if self.is_batch_transfer(&message) {
+                        self.gas_config.ft_transfer +
+                        self.gas_config.callback_batch_transfer(transfers.len() + 1)
+                    } else {
+                        self.swap_from_gas(&message) + 
+                        self.pending_batch_callback_gas(transfers.len())
+                    };
+                    self.check_enough_gas(required_gas)?;
+
+                    Ok(message)
+                });
+
+            match checked_message {
+                Ok(message) if self.is_batch_transfer(&message) => {
+                    let (swap_from_event, transit_amount) = 
+                        self.reserve_swap_from(&item.params, &message, 0);
+
+                    transfers.push(ext_fungible_token::ft_transfer(
+                        item.params.new_address.to_string(),
+                        U128(transit_amount),
+                        None,
+                        &self.transfer_token,
+                        1,
+                        self.gas_config.ft_transfer,
+                    ));
+                    swap_from_events.push(swap_from_event);
+                    transit_amounts.push(U128(transit_amount));
+                }
+                Ok(message) => {
+                    self.dispatch_swap_tokens_to_user(item.params, item.msg, message);
+                }
+                Err(reason) => {
+                    Event::SwapFromOtherBlockchainFailed(&SwapFromFailedEvent {
+                        original_tx_hash,
+                        reason: reason.to_string(),
+                    }).emit();
+                }
+            }
+        }
+
+        let callback_gas = self.pending_batch_callback_gas(transfers.len());
+        if let Some(transfers) = transfers
+            .into_iter()
+            .reduce(|transfers, transfer| transfers.and(transfer))
+        {
+            transfers.then(ext_self_batch::callback_after_batch_transfer(
+                swap_from_events,
+                transit_amounts,
+                &env::current_account_id(),
+                0,
+                callback_gas,
+            ));
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Settles swaps from other blockchains paid by transfers of batch,
+    /// results of transfers are in order of `swap_from_events`
+    #[private]
+    pub fn callback_after_batch_transfer(
+        &mut self,
+        swap_from_events: Vec<SwapFromEvent>,
+        transit_amounts: Vec<U128>,
+    ) {
+        assert_eq!(
+            env::promise_results_count(),
+            swap_from_events.len() as u64,
+            "AfterBatchTransfer: Expected promise result for every transfer"
+        );
+
+        for (index, (swap_from_event, transit_amount)) in swap_from_events
+            .into_iter()
+            .zip(transit_amounts)
+            .enumerate()
+        {
+            let is_paid = matches!(
+                env::promise_result(index as u64),
+                PromiseResult::Successful(_)
+            );
+            self.complete_swap_from(swap_from_event, transit_amount.into(), is_paid, 0, true);
+        }
+    }
+}
+
+impl Contract {
+    /// Swap is paid by transfer of batch if user gets `transfer_token`
+    /// and no RBC balance is requested for discount
+    fn is_batch_transfer(&self, message: &Option<SwapFromMessage>) -> bool {
+        message.is_none() && !self.is_discount_enabled()
+    }
+
+    /// Gas for callback after `transfers` of batch, zero without them
+    fn pending_batch_callback_gas(&self, transfers: usize) -> Gas {
+        if transfers == 0 {
+            return 0;
+        }
+
+        self.gas_config.callback_batch_transfer(transfers)
+    }
+}
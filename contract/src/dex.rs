@@ -0,0 +1,102 @@
+use crate::interfaces::{DexInfo, DexKind};
+
+use super::*;
+
+/// Interface of DEX used for swaps between `transfer_token` and other tokens.
+/// Tokens are sent from and returned to this contract within one swap.
+pub trait DexAdapter {
+    /// Transfers `amount` of `token_id` to DEX and swaps it by `actions`
+    /// in one call. Output tokens are transferred back to this contract,
+    /// by `ft_transfer_call` with `output_echo` as message if it is set.
+    /// Promise resolves to the amount of `token_id` used by DEX.
+    fn instant_swap(
+        &self,
+        token_id: &AccountId,
+        amount: U128,
+        actions: Vec<Action>,
+        output_echo: Option<String>,
+        gas: Gas,
+    ) -> Promise;
+}
+
+/// Ref Finance and its forks with the same interface
+/// * `router` - DEX contract account
+/// * `referral_id` - account that receives referral fee of DEX
+pub struct RefFinanceAdapter {
+    pub router: AccountId,
+    pub referral_id: Option<AccountId>,
+}
+
+impl RefFinanceAdapter {
+    fn referral_id(&self) -> Option<ValidAccountId> {
+        self.referral_id
+            .clone()
+            .map(|referral_id| referral_id.try_into().unwrap())
+    }
+}
+
+impl DexAdapter for RefFinanceAdapter {
+    fn instant_swap(
+        &self,
+        token_id: &AccountId,
+        amount: U128,
+        actions: Vec<Action>,
+        output_echo: Option<String>,
+        gas: Gas,
+    ) -> Promise {
+        // Contract unwraps wrap.near by itself.
+        // Ref Finance sends output with `client_echo` by `ft_transfer_call`,
+        // this contract must be whitelisted as echo sender.
+        let msg = RefFinanceReceiverMessage::ExecuteSwap {
+            referral_id: self.referral_id(),
+            actions,
+            client_echo: output_echo,
+            skip_unwrap_near: Some(true),
+        };
+
+        ext_fungible_token::ft_transfer_call(
+            self.router.clone(),
+            amount,
+            None,
+            serde_json::to_string(&msg).unwrap(),
+            token_id,
+            1,
+            gas,
+        )
+    }
+}
+
+impl DexInfo {
+    pub fn adapter(&self, referral_id: Option<AccountId>) -> Box<dyn DexAdapter> {
+        match self.kind {
+            DexKind::RefFinance => Box::new(RefFinanceAdapter {
+                router: self.router.clone(),
+                referral_id,
+            }),
+        }
+    }
+}
+
+impl Contract {
+    /// _true_ if `account_id` is router of registered DEX 
+    /// or `blockchain_router`
+    pub(crate) fn is_dex_router(&self, account_id: &AccountId) -> bool {
+        *account_id == self.blockchain_router || 
+            self.dexes.values().any(|dex| dex.router == *account_id)
+    }
+
+    /// DEX registered as `dex_id` or Ref Finance at `blockchain_router`
+    /// if `dex_id` is _None_
+    pub(crate) fn get_dex_adapter(&self, dex_id: &Option<String>) -> Box<dyn DexAdapter> {
+        match dex_id {
+            Some(dex_id) => self.dexes
+                .get(dex_id)
+                .expect("Unknown dex")
+                .adapter(self.dex_referral_id.clone()),
+            None => Box::new(RefFinanceAdapter {
+                router: self.blockchain_router.clone(),
+                referral_id: self.dex_referral_id.clone(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use super::*;
+
+#[ext_contract(ext_self_discount)]
+pub trait AfterHolderBalance {
+    fn callback_after_holder_balance(
+        &mut self,
+        params: SwapFromParams,
+        msg: Option<String>,
+    );
+    fn callback_after_sender_balance(
+        &mut self,
+        sender_id: AccountId,
+        token_in: AccountId,
+        amount_in: U128,
+        transit_amount: U128,
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Contract and view method which return RBC balance of account,
+    /// e.g. `ft_balance_of` of RBC token or staked balance of staking.
+    /// Discount is disabled if _None_.
+    #[payable]
+    pub fn set_holder_balance_source(&mut self, source: Option<HolderBalanceSource>) {
+        self.assert_owner();
+        if let Some(source) = &source {
+            assert!(
+                env::is_valid_account_id(source.contract_id.as_bytes()),
+                "Wrong holder balance contract"
+            );
+        }
+        self.holder_balance_source = source;
+    }
+
+    /// discount of every tier is represented in basis points of fee
+    #[payable]
+    pub fn set_discount_tiers(&mut self, discount_tiers: Vec<DiscountTier>) {
+        self.assert_owner_or_manager();
+        assert!(
+            discount_tiers
+                .iter()
+                .all(|tier| u128::from(tier.discount) <= BASIS_POINTS_DENOMINATOR),
+            "Discount must not exceed 10000"
+        );
+        assert!(
+            discount_tiers
+                .windows(2)
+                .all(|pair| u128::from(pair[0].min_balance) < u128::from(pair[1].min_balance)),
+            "Discount tiers must be sorted by min balance"
+        );
+        self.discount_tiers = discount_tiers;
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Finalizes fee with discount by RBC balance of receiver.
+    /// Swap is checked again as state could change since the view call.
+    #[private]
+    pub fn callback_after_holder_balance(
+        &mut self,
+        params: SwapFromParams,
+        msg: Option<String>,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "AfterBalance: Expected 1 promise result");
+        let fee_discount = self.holder_fee_discount_of_result(0);
+
+        match self.check_swap_from(&params, &msg) {
+            Ok(message) => {
+                self.internal_swap_tokens_to_user(params, message, fee_discount);
+            }
+            Err(reason) => {
+                Event::SwapFromOtherBlockchainFailed(&SwapFromFailedEvent {
+                    original_tx_hash: params.original_tx_hash,
+                    reason: reason.to_string(),
+                }).emit();
+            }
+        }
+    }
+
+    /// Completes swap of `transfer_token` to other blockchain 
+    /// with discount by RBC balance of sender
+    #[private]
+    pub fn callback_after_sender_balance(
+        &mut self,
+        sender_id: AccountId,
+        token_in: AccountId,
+        amount_in: U128,
+        transit_amount: U128,
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "AfterBalance: Expected 1 promise result");
+        let fee_discount = self.holder_fee_discount_of_result(0);
+
+        self.finalize_swap_to(
+            &sender_id,
+            &token_in,
+            u128::from(amount_in),
+            u128::from(transit_amount),
+            swap_to_params,
+            &integrator_fee,
+            fee_discount,
+        );
+    }
+}
+
+impl Contract {
+    /// Transfers tokens to user by checked `message`. If discount is set,
+    /// RBC balance of receiver is requested first. 
+    pub(crate) fn dispatch_swap_tokens_to_user(
+        &mut self,
+        params: SwapFromParams,
+        msg: Option<String>,
+        message: Option<SwapFromMessage>,
+    ) -> Promise {
+        let request = match self.request_holder_balance(params.new_address.as_ref()) {
+            Some(request) => request,
+            None => return self.internal_swap_tokens_to_user(params, message, 0),
+        };
+        let callback_gas = 
+            self.gas_config.swap_from(&message) +
+            self.gas_config.execution;
+
+        request
+            .then(ext_self_discount::callback_after_holder_balance(
+                params,
+                msg,
+                &env::current_account_id(),
+                0,
+                callback_gas,
+            ))
+    }
+
+    /// Gas for payout of swap from other blockchain including
+    /// request of RBC balance if discount is set
+    pub(crate) fn swap_from_gas(&self, message: &Option<SwapFromMessage>) -> Gas {
+        let payout_gas = self.gas_config.swap_from(message);
+        if !self.is_discount_enabled() {
+            return payout_gas;
+        }
+
+        self.gas_config.holder_balance_view + payout_gas + self.gas_config.execution
+    }
+
+    /// Gas for request of RBC balance, zero if discount is not set
+    pub(crate) fn holder_balance_gas(&self) -> Gas {
+        if self.is_discount_enabled() {
+            self.gas_config.holder_balance_view
+        } else {
+            0
+        }
+    }
+
+    /// Requests RBC balance of `account_id`, _None_ if discount is not set
+    pub(crate) fn request_holder_balance(&self, account_id: &AccountId) -> Option<Promise> {
+        if !self.is_discount_enabled() {
+            return None;
+        }
+        let source = self.holder_balance_source.clone().unwrap();
+
+        Some(
+            Promise::new(source.contract_id)
+                .function_call(
+                    source.method_name.into_bytes(),
+                    serde_json::json!({ "account_id": account_id })
+                        .to_string()
+                        .into_bytes(),
+                    0,
+                    self.gas_config.holder_balance_view,
+                )
+        )
+    }
+
+    /// Discount of fee by RBC balance in promise result at `result_index`
+    /// requested by `request_holder_balance`, zero if there is no result
+    pub(crate) fn holder_fee_discount_of_result(&self, result_index: u64) -> u16 {
+        if result_index >= env::promise_results_count() {
+            return 0;
+        }
+
+        // Swap is not blocked by unavailable balance, it just gets no discount
+        let balance = match env::promise_result(result_index) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(u128::from)
+                .unwrap_or(0),
+            _ => 0,
+        };
+
+        self.holder_fee_discount(balance)
+    }
+
+    /// Discount of fee in basis points by the highest tier reached by `balance`
+    pub(crate) fn holder_fee_discount(&self, balance: u128) -> u16 {
+        self.discount_tiers
+            .iter()
+            .rev()
+            .find(|tier| balance >= u128::from(tier.min_balance))
+            .map_or(0, |tier| tier.discount)
+    }
+
+    pub(crate) fn is_discount_enabled(&self) -> bool {
+        self.holder_balance_source.is_some() && !self.discount_tiers.is_empty()
+    }
+}
@@ -0,0 +1,121 @@
+use crate::events::EscrowEvent;
+
+use super::*;
+
+/// Default time after which user can refund not bridged tokens, 1 day
+pub const DEFAULT_ESCROW_TIMEOUT: u64 = 24 * 60 * 60 * 1_000_000_000;
+/// Relayer must have time to mark swap as bridged before user 
+/// can refund it, 1 hour
+pub const MIN_ESCROW_TIMEOUT: u64 = 60 * 60 * 1_000_000_000;
+
+#[ext_contract(ext_self_escrow)]
+pub trait AfterRefundEscrow {
+    fn callback_after_refund_escrow(&mut self, escrow_id: u64, escrow: EscrowRecord);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Marks tokens of swap to other blockchain as delivered, 
+    /// so they can no longer be refunded
+    #[payable]
+    pub fn mark_bridged(&mut self, escrow_id: u64) {
+        self.assert_relayer();
+
+        let escrow = self.escrows
+            .remove(&escrow_id)
+            .expect("Unknown escrow");
+        // Bridged tokens back payouts of swaps from other blockchains
+        if escrow.token_id == self.transfer_token {
+            self.liquidity.reserved -= u128::from(escrow.amount);
+            self.liquidity.available += u128::from(escrow.amount);
+        }
+
+        Event::SwapToOtherBlockchainBridged(&EscrowEvent {
+            escrow_id,
+            sender_id: escrow.sender_id,
+            amount: escrow.amount,
+        }).emit();
+    }
+
+    /// Returns `transfer_token` of swap to other blockchain to sender
+    /// if swap was not bridged within `escrow_timeout`
+    #[payable]
+    pub fn refund_escrow(&mut self, escrow_id: u64) -> Promise {
+        let escrow = self.escrows
+            .remove(&escrow_id)
+            .expect("Unknown escrow");
+        assert_eq!(
+            env::predecessor_account_id(),
+            escrow.sender_id,
+            "Only for sender of swap"
+        );
+        assert!(
+            env::block_timestamp() >= u64::from(escrow.created_at) + self.escrow_timeout,
+            "Escrow timeout has not passed"
+        );
+        if escrow.token_id == self.transfer_token {
+            self.liquidity.reserved -= u128::from(escrow.amount);
+        }
+
+        ext_fungible_token::ft_transfer(
+            escrow.sender_id.clone(),
+            escrow.amount,
+            None,
+            &escrow.token_id,
+            1,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_escrow::callback_after_refund_escrow(
+            escrow_id,
+            escrow,
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Restores escrow if refund failed
+    #[private]
+    pub fn callback_after_refund_escrow(&mut self, escrow_id: u64, escrow: EscrowRecord) {
+        assert_eq!(env::promise_results_count(), 1, "AfterRefund: Expected 1 promise result");
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                Event::SwapToOtherBlockchainRefunded(&EscrowEvent {
+                    escrow_id,
+                    sender_id: escrow.sender_id,
+                    amount: escrow.amount,
+                }).emit();
+            }
+            _ => {
+                env::log(b"Refund of escrow failed");
+                if escrow.token_id == self.transfer_token {
+                    self.liquidity.reserved += u128::from(escrow.amount);
+                }
+                self.escrows.insert(&escrow_id, &escrow);
+            }
+        }
+    }
+}
+
+impl Contract {
+    /// Holds `amount` of `transfer_token` sent by `sender_id` 
+    /// to other blockchain until it is bridged. Returns id of escrow.
+    pub(crate) fn create_escrow(&mut self, sender_id: &AccountId, amount: u128) -> u64 {
+        let escrow_id = self.next_escrow_id;
+        self.next_escrow_id += 1;
+        self.liquidity.reserved += amount;
+
+        self.escrows.insert(&escrow_id, &EscrowRecord {
+            sender_id: sender_id.clone(),
+            token_id: self.transfer_token.clone(),
+            amount: U128(amount),
+            created_at: U64(env::block_timestamp()),
+        });
+
+        escrow_id
+    }
+}
@@ -0,0 +1,151 @@
+// List items in docs are aligned with descriptions of their items
+#![allow(clippy::doc_overindented_list_items)]
+
+use near_sdk::serde::{Deserialize, Serialize};
+
+use crate::interfaces::SwapToParams;
+use super::*;
+
+pub const EVENT_STANDARD: &str = "rubic-cross-chain";
+pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Events are logged in NEP-297 format: `EVENT_JSON:{...}`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event<'a> {
+    SwapToOtherBlockchain(&'a SwapToEvent),
+    SwapFromOtherBlockchain(&'a SwapFromEvent),
+    SwapFromOtherBlockchainFailed(&'a SwapFromFailedEvent),
+    SwapToOtherBlockchainBridged(&'a EscrowEvent),
+    SwapToOtherBlockchainRefunded(&'a EscrowEvent),
+    FeeDistributionUpdated(&'a FeeDistributionChange),
+    FeeConvertedToNear(&'a FeeConversionEvent),
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+impl Event<'_> {
+    pub fn emit(&self) {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            event: self,
+        };
+
+        env::log(
+            format!("EVENT_JSON:{}", serde_json::to_string(&log).unwrap())
+                .as_bytes()
+        );
+    }
+}
+
+/// Tokens were received and have to be transferred to other blockchain
+/// * `escrow_id` - id of escrow holding transit tokens until bridged
+/// * `sender_id` - user who sent tokens
+/// * `token_in` - token sent by user
+/// * `amount_in` - amount of `token_in`
+/// * `integrator_fee` - fee of integrator in `token_in`
+/// * `fee_rate` - fee rate of sender by fee tiers with discount, 
+///                to be taken in target blockchain
+/// * `fee_discount` - discount of fee rate for RBC holder, 
+///                    in basis points
+/// * `transit_amount` - amount of `transfer_token` to be bridged
+/// * `transit_amount_normalized` - `transit_amount` in decimals of
+///                                 transit token in target blockchain
+/// * `swap_to_params` - params of swap in target blockchain
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapToEvent {
+    pub escrow_id: u64,
+    pub sender_id: AccountId,
+    pub token_in: AccountId,
+    pub amount_in: U128,
+    pub integrator_fee: U128,
+    pub fee_rate: U128,
+    pub fee_discount: u16,
+    pub transit_amount: U128,
+    pub transit_amount_normalized: U128,
+    pub swap_to_params: SwapToParams,
+}
+
+/// Tokens from other blockchain were transferred to user
+/// * `original_tx_hash` - transaction hash in source blockchain
+/// * `blockchain` - source blockchain
+/// * `new_address` - receiver of tokens
+/// * `token_out` - token received by user
+/// * `amount_in_with_fee` - amount in decimals of transit token
+///                          in source blockchain
+/// * `amount_in_with_fee_normalized` - `amount_in_with_fee` in decimals
+///                                     of `transfer_token`
+/// * `fee` - fee in `transfer_token`
+/// * `fee_discount` - discount of fee for RBC holder, in basis points
+/// * `relayer_compensation` - compensation of relayer gas 
+///                            in `transfer_token`
+/// * `integrator` - account of integrator which gets `integrator_fee`
+/// * `integrator_fee` - fee of integrator in `transfer_token`
+/// * `amount_out_min` - minimum amount of `token_out` received by user
+/// * `referrer` - referrer of swap
+/// * `referral_fee` - part of `fee` credited to `referrer`
+/// * `lp_fee` - part of `fee` credited to liquidity providers
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapFromEvent {
+    pub original_tx_hash: String,
+    pub blockchain: u64,
+    pub new_address: AccountId,
+    pub token_out: AccountId,
+    pub amount_in_with_fee: U128,
+    pub amount_in_with_fee_normalized: U128,
+    pub fee: U128,
+    pub fee_discount: u16,
+    pub relayer_compensation: U128,
+    pub integrator: Option<AccountId>,
+    pub integrator_fee: U128,
+    pub amount_out_min: U128,
+    pub referrer: Option<AccountId>,
+    pub referral_fee: U128,
+    pub lp_fee: U128,
+}
+
+/// Swap from other blockchain was rejected or transfer to user failed.
+/// Swap is not marked as processed and can be retried.
+/// * `original_tx_hash` - transaction hash in source blockchain
+/// * `reason` - reason of failure
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapFromFailedEvent {
+    pub original_tx_hash: String,
+    pub reason: String,
+}
+
+/// Escrow of swap to other blockchain was bridged or refunded
+/// * `escrow_id` - id of escrow
+/// * `sender_id` - user who sent tokens
+/// * `amount` - transit amount
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowEvent {
+    pub escrow_id: u64,
+    pub sender_id: AccountId,
+    pub amount: U128,
+}
+
+/// Accrued fee was swapped for NEAR and sent to treasury
+/// * `amount` - fee in `transfer_token` used by swap
+/// * `amount_out` - amount of NEAR sent to treasury
+/// * `treasury_id` - receiver of NEAR
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeConversionEvent {
+    pub amount: U128,
+    pub amount_out: U128,
+    pub treasury_id: AccountId,
+}
@@ -0,0 +1,187 @@
+use crate::events::FeeConversionEvent;
+
+use super::*;
+
+#[ext_contract(ext_self_fee_conversion)]
+pub trait AfterFeeConversion {
+    fn callback_after_fee_conversion(
+        &mut self,
+        amount: U128,
+        amount_out: U128,
+        treasury_id: AccountId,
+        is_beneficiary_fee: bool,
+    );
+    fn callback_after_fee_unwrap(
+        &mut self,
+        amount: U128,
+        amount_out: U128,
+        treasury_id: AccountId,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Swaps `amount` of accrued `transfer_token` fee for wNEAR by 
+    /// `actions` at `blockchain_router`, unwraps it and sends NEAR to
+    /// `fee_treasury`. The last action must be swap by output, so treasury
+    /// gets exactly its `amount_out` and fee not used by swap is returned.
+    /// Fee is restored if swap fails. If fee is distributed to beneficiaries,
+    /// fee accrued by `fee_treasury` as beneficiary is converted, 
+    /// otherwise `acc_token_fee`.
+    #[payable]
+    pub fn convert_fee_to_near(&mut self, amount: U128, actions: Vec<Action>) -> Promise {
+        self.assert_owner_or_manager();
+        let treasury_id = self.fee_treasury
+            .clone()
+            .expect("Fee treasury is not set");
+        assert!(u128::from(amount) > 0, "Amount must be positive");
+        let is_beneficiary_fee = !self.fee_beneficiaries.is_empty();
+        assert!(
+            u128::from(amount) <= self.convertible_fee(&treasury_id, is_beneficiary_fee),
+            "Not enough accrued fee"
+        );
+        self.validate_swap_route(&actions, &self.transfer_token, &WRAP_NEAR.to_string());
+        let amount_out = match actions.last() {
+            Some(Action::SwapByOutput(action)) => action.amount_out,
+            _ => None,
+        }
+        .expect("Last action must be swap by output with amount out");
+        assert!(u128::from(amount_out) > 0, "Amount out must be positive");
+
+        let swap_gas = self.gas_config.swap(actions.len());
+        let callback_gas = 
+            self.gas_config.callback + 
+            self.gas_config.wrap_withdraw +
+            self.gas_config.callback;
+        self.assert_enough_gas(swap_gas + callback_gas);
+
+        self.debit_convertible_fee(&treasury_id, is_beneficiary_fee, u128::from(amount));
+        let mut actions = actions;
+        actions[0].set_amount_in(amount);
+
+        self.get_dex_adapter(&None)
+            .instant_swap(&self.transfer_token, amount, actions, None, swap_gas)
+            .then(ext_self_fee_conversion::callback_after_fee_conversion(
+                amount,
+                amount_out,
+                treasury_id,
+                is_beneficiary_fee,
+                &env::current_account_id(),
+                0,
+                callback_gas,
+            ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Unwraps `amount_out` of wNEAR if swap succeeded.
+    /// `transfer_token` not used by swap is returned to accrued fee.
+    #[private]
+    pub fn callback_after_fee_conversion(
+        &mut self,
+        amount: U128,
+        amount_out: U128,
+        treasury_id: AccountId,
+        is_beneficiary_fee: bool,
+    ) {
+        let result = split::collect_split_swap_results(&[amount], &[amount_out]);
+
+        if result.amount_returned > 0 {
+            self.credit_convertible_fee(&treasury_id, is_beneficiary_fee, result.amount_returned);
+        }
+        if result.failed_routes > 0 {
+            env::log(b"Fee conversion failed");
+            return;
+        }
+
+        ext_wrap::near_withdraw(
+            amount_out,
+            &WRAP_NEAR.to_string(),
+            1,
+            self.gas_config.wrap_withdraw,
+        )
+        .then(ext_self_fee_conversion::callback_after_fee_unwrap(
+            U128(u128::from(amount) - result.amount_returned),
+            amount_out,
+            treasury_id,
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ));
+    }
+
+    /// Sends NEAR to treasury if wNEAR was unwrapped, 
+    /// otherwise treasury can claim wNEAR by `claim_payout`.
+    /// * `amount` - fee in `transfer_token` used by swap
+    #[private]
+    pub fn callback_after_fee_unwrap(
+        &mut self,
+        amount: U128,
+        amount_out: U128,
+        treasury_id: AccountId,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "AfterUnwrap: Expected 1 promise result");
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                Promise::new(treasury_id.clone()).transfer(u128::from(amount_out));
+
+                Event::FeeConvertedToNear(&FeeConversionEvent {
+                    amount,
+                    amount_out,
+                    treasury_id,
+                }).emit();
+            }
+            _ => {
+                env::log(b"Unwrap of converted fee failed, wNEAR can be claimed by treasury");
+                self.credit_pending_payout(
+                    &treasury_id,
+                    &WRAP_NEAR.to_string(),
+                    u128::from(amount_out),
+                );
+            }
+        }
+    }
+}
+
+impl Contract {
+    /// Fee which can be converted to NEAR: fee accrued by `treasury_id` 
+    /// as beneficiary if `is_beneficiary_fee`, otherwise `acc_token_fee`
+    fn convertible_fee(&self, treasury_id: &AccountId, is_beneficiary_fee: bool) -> u128 {
+        if is_beneficiary_fee {
+            self.beneficiary_fees.get(treasury_id).unwrap_or(0)
+        } else {
+            self.acc_token_fee
+        }
+    }
+
+    fn debit_convertible_fee(
+        &mut self,
+        treasury_id: &AccountId,
+        is_beneficiary_fee: bool,
+        amount: u128,
+    ) {
+        if is_beneficiary_fee {
+            let balance = self.convertible_fee(treasury_id, true);
+            self.beneficiary_fees.insert(treasury_id, &(balance - amount));
+        } else {
+            self.acc_token_fee -= amount;
+        }
+        self.liquidity.fees -= amount;
+    }
+
+    fn credit_convertible_fee(
+        &mut self,
+        treasury_id: &AccountId,
+        is_beneficiary_fee: bool,
+        amount: u128,
+    ) {
+        if is_beneficiary_fee {
+            self.credit_beneficiary_fee(treasury_id, amount);
+        } else {
+            self.acc_token_fee += amount;
+        }
+        self.liquidity.fees += amount;
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+
+use super::*;
+
+#[ext_contract(ext_self_fee_split)]
+pub trait AfterWithdrawBeneficiaryFee {
+    fn callback_after_withdraw_beneficiary_fee(
+        &mut self,
+        beneficiary_id: AccountId,
+        amount: U128,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets table of fee beneficiaries, `share` of each beneficiary is 
+    /// represented in basis points and shares must sum to 10000.
+    /// With empty table fee is accrued to `acc_token_fee`.
+    /// Fee accrued before update stays withdrawable.
+    #[payable]
+    pub fn set_fee_distribution(&mut self, beneficiaries: Vec<FeeBeneficiary>) {
+        self.assert_owner();
+        validate_fee_beneficiaries(&beneficiaries);
+        self.fee_beneficiaries = beneficiaries;
+
+        let change = FeeDistributionChange {
+            updated_by: env::predecessor_account_id(),
+            updated_at: U64(env::block_timestamp()),
+            beneficiaries: self.fee_beneficiaries.clone(),
+        };
+        Event::FeeDistributionUpdated(&change).emit();
+        self.fee_distribution_history.push(&change);
+    }
+
+    /// Transfers fee accrued by caller as beneficiary in `transfer_token`
+    #[payable]
+    pub fn withdraw_beneficiary_fee(&mut self) -> Promise {
+        let beneficiary_id = env::predecessor_account_id();
+        let amount = self.beneficiary_fees
+            .remove(&beneficiary_id)
+            .unwrap_or(0);
+        assert!(amount > 0, "No fee to withdraw");
+        self.liquidity.fees -= amount;
+
+        ext_fungible_token::ft_transfer(
+            beneficiary_id.clone(),
+            U128(amount),
+            None,
+            &self.transfer_token,
+            1,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_fee_split::callback_after_withdraw_beneficiary_fee(
+            beneficiary_id,
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns fee to beneficiary balance if transfer failed
+    #[private]
+    pub fn callback_after_withdraw_beneficiary_fee(
+        &mut self,
+        beneficiary_id: AccountId,
+        amount: U128,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "AfterWithdraw: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Withdrawal of beneficiary fee failed");
+            self.credit_beneficiary_fee(&beneficiary_id, u128::from(amount));
+            self.liquidity.fees += u128::from(amount);
+        }
+    }
+}
+
+impl Contract {
+    /// Distributes protocol `fee` between fee beneficiaries by their shares.
+    /// Rounding remainder goes to the last beneficiary.
+    pub(crate) fn accrue_protocol_fee(&mut self, fee: u128) {
+        if self.fee_beneficiaries.is_empty() {
+            self.acc_token_fee += fee;
+            return;
+        }
+
+        let mut distributed = 0;
+        let last_index = self.fee_beneficiaries.len() - 1;
+        for (index, beneficiary) in self.fee_beneficiaries.clone().iter().enumerate() {
+            let amount = if index == last_index {
+                fee - distributed
+            } else {
+                fee * u128::from(beneficiary.share) / BASIS_POINTS_DENOMINATOR
+            };
+            distributed += amount;
+            if amount > 0 {
+                self.credit_beneficiary_fee(&beneficiary.account_id, amount);
+            }
+        }
+    }
+
+    pub(crate) fn credit_beneficiary_fee(&mut self, beneficiary_id: &AccountId, amount: u128) {
+        let balance = self.beneficiary_fees
+            .get(beneficiary_id)
+            .unwrap_or(0);
+        self.beneficiary_fees.insert(beneficiary_id, &(balance + amount));
+    }
+}
+
+fn validate_fee_beneficiaries(beneficiaries: &[FeeBeneficiary]) {
+    if beneficiaries.is_empty() {
+        return;
+    }
+
+    let mut accounts = HashSet::new();
+    let mut total_share = 0;
+    for beneficiary in beneficiaries {
+        assert!(
+            env::is_valid_account_id(beneficiary.account_id.as_bytes()),
+            "Wrong beneficiary account"
+        );
+        assert!(
+            accounts.insert(&beneficiary.account_id),
+            "Duplicate beneficiary"
+        );
+        assert!(beneficiary.share > 0, "Share of beneficiary must be positive");
+        total_share += u128::from(beneficiary.share);
+    }
+    assert_eq!(
+        total_share,
+        BASIS_POINTS_DENOMINATOR,
+        "Shares of beneficiaries must sum to 10000"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    use super::*;
+
+    fn setup(beneficiaries: &[(&str, u16)]) -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.testnet".try_into().unwrap())
+            .attached_deposit(1)
+            .build());
+
+        let mut contract = Contract::new(
+            "owner.testnet".try_into().unwrap(),
+            "manager.testnet".try_into().unwrap(),
+            "relayer.testnet".try_into().unwrap(),
+            "usdt.testnet".try_into().unwrap(),
+            "ref-finance.testnet".try_into().unwrap(),
+            6,
+            0,
+            U128(0),
+            U128(u128::MAX),
+            U128(3000),
+            true,
+        );
+        contract.set_fee_distribution(
+            beneficiaries
+                .iter()
+                .map(|(account_id, share)| FeeBeneficiary {
+                    account_id: account_id.to_string(),
+                    share: *share,
+                })
+                .collect(),
+        );
+
+        contract
+    }
+
+    fn beneficiary_fee(contract: &Contract, account_id: &str) -> u128 {
+        contract.beneficiary_fees
+            .get(&account_id.to_string())
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn fee_without_beneficiaries() {
+        let mut contract = setup(&[]);
+        contract.accrue_protocol_fee(1_000);
+
+        assert_eq!(contract.acc_token_fee, 1_000);
+    }
+
+    #[test]
+    fn fee_split_by_shares() {
+        let mut contract = setup(&[("dao.testnet", 7000), ("team.testnet", 3000)]);
+        contract.accrue_protocol_fee(1_000);
+
+        assert_eq!(beneficiary_fee(&contract, "dao.testnet"), 700);
+        assert_eq!(beneficiary_fee(&contract, "team.testnet"), 300);
+        assert_eq!(contract.acc_token_fee, 0);
+    }
+
+    #[test]
+    fn last_beneficiary_gets_remainder() {
+        let mut contract = setup(&[
+            ("dao.testnet", 3333),
+            ("team.testnet", 3333),
+            ("treasury.testnet", 3334),
+        ]);
+        contract.accrue_protocol_fee(100);
+
+        // 33.33 is rounded down for each but the last
+        assert_eq!(beneficiary_fee(&contract, "dao.testnet"), 33);
+        assert_eq!(beneficiary_fee(&contract, "team.testnet"), 33);
+        assert_eq!(beneficiary_fee(&contract, "treasury.testnet"), 34);
+
+        contract.accrue_protocol_fee(1);
+        assert_eq!(beneficiary_fee(&contract, "dao.testnet"), 33);
+        assert_eq!(beneficiary_fee(&contract, "team.testnet"), 33);
+        assert_eq!(beneficiary_fee(&contract, "treasury.testnet"), 35);
+    }
+}
@@ -0,0 +1,255 @@
+// List items in docs are aligned with descriptions of their items
+#![allow(clippy::doc_overindented_list_items)]
+
+use near_sdk::serde::Serialize;
+
+use super::*;
+
+/// Fee is represented as hundredths of a bip, i.e. 1e-6
+pub const FEE_DENOMINATOR: u128 = 1_000_000;
+/// Shares of fee are represented in basis points
+pub const BASIS_POINTS_DENOMINATOR: u128 = 10_000;
+
+/// Amounts of one swap between blockchains
+/// * `amount_in` - amount as sent by user, in decimals of source transit token
+/// * `gross_amount` - `amount_in` in decimals of `transfer_token`
+/// * `fee` - fee in `transfer_token`
+/// * `fee_rate` - rate of `fee` by fee tiers of account,
+///                in hundredths of a bip
+/// * `fee_discount` - discount of fee rate for RBC holder,
+///                    in basis points
+/// * `relayer_compensation` - compensation of relayer gas 
+///                            in `transfer_token`
+/// * `integrator_fee` - fee of integrator in `transfer_token`, taken from
+///                      `gross_amount` without `fee` and `relayer_compensation`
+/// * `net_amount` - `gross_amount` without `fee`, `relayer_compensation`
+///                  and `integrator_fee`
+/// * `net_amount_normalized` - `net_amount` in decimals of
+///                             destination transit token
+pub(crate) struct SwapAmounts {
+    pub amount_in: u128,
+    pub gross_amount: u128,
+    pub fee: u128,
+    pub fee_rate: u128,
+    pub fee_discount: u16,
+    pub relayer_compensation: u128,
+    pub integrator_fee: u128,
+    pub net_amount: u128,
+    pub net_amount_normalized: u128,
+}
+
+/// Result of `quote_swap_from` and `quote_swap_to` views.
+/// Fields are the same as in `SwapAmounts`.
+/// * `is_amount_valid` - _true_ if `gross_amount` passes min and max 
+///                       token amount checks and covers all fees.
+///                       Swap from other blockchain also requires
+///                       available liquidity for `gross_amount`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapQuote {
+    pub amount_in: U128,
+    pub gross_amount: U128,
+    pub fee: U128,
+    pub fee_rate: U128,
+    pub fee_discount: u16,
+    pub relayer_compensation: U128,
+    pub integrator_fee: U128,
+    pub net_amount: U128,
+    pub net_amount_normalized: U128,
+    pub is_amount_valid: bool,
+}
+
+impl Contract {
+    /// Fee rate of swap of `gross_amount` by `account_id`: the lowest of
+    /// `fee_amount_of_blockchain` and rates of tiers reached by 
+    /// `gross_amount` and by rolling volume of account
+    pub(crate) fn fee_rate(&self, account_id: &AccountId, gross_amount: u128) -> u128 {
+        let mut fee_rate = self.fee_amount_of_blockchain;
+        if let Some(tier_rate) = tier_fee_rate(&self.fee_schedule.amount_tiers, gross_amount) {
+            fee_rate = std::cmp::min(fee_rate, tier_rate);
+        }
+        if !self.fee_schedule.volume_tiers.is_empty() {
+            let volume = self.account_volume(account_id);
+            if let Some(tier_rate) = tier_fee_rate(&self.fee_schedule.volume_tiers, volume) {
+                fee_rate = std::cmp::min(fee_rate, tier_rate);
+            }
+        }
+
+        fee_rate
+    }
+
+    /// Fee rate of `account_id` reduced by `fee_discount` in basis points
+    fn discounted_fee_rate(
+        &self,
+        account_id: &AccountId,
+        gross_amount: u128,
+        fee_discount: u16,
+    ) -> u128 {
+        self.fee_rate(account_id, gross_amount) *
+            (BASIS_POINTS_DENOMINATOR - u128::from(fee_discount)) /
+            BASIS_POINTS_DENOMINATOR
+    }
+
+    /// Amounts of swap from other `blockchain` to `account_id`,
+    /// fee rate is reduced by `fee_discount` in basis points.
+    /// Used by `swap_tokens_to_user_with_fee` and `quote_swap_from`.
+    /// Does not panic on too big `amount_in_with_fee`, so that
+    /// it does not revert a batch.
+    pub(crate) fn calculate_swap_from(
+        &self,
+        blockchain: u64,
+        amount_in_with_fee: u128,
+        account_id: &AccountId,
+        integrator: &Option<String>,
+        fee_discount: u16,
+    ) -> Result<SwapAmounts, &'static str> {
+        let gross_amount = self
+            .normalize_amount_from(blockchain, amount_in_with_fee, Rounding::Down)
+            .ok_or("Amount is too big")?;
+        // Fees and their shares are calculated without overflow below this
+        gross_amount
+            .checked_mul(FEE_DENOMINATOR)
+            .ok_or("Amount is too big")?;
+        let fee_rate = self.discounted_fee_rate(account_id, gross_amount, fee_discount);
+        let amount_after_fee =
+            gross_amount *
+            (FEE_DENOMINATOR - fee_rate) /
+            FEE_DENOMINATOR;
+        let relayer_compensation = std::cmp::min(
+            self.relayer_compensation_amount(blockchain),
+            amount_after_fee,
+        );
+        let amount_without_fee = amount_after_fee - relayer_compensation;
+        let integrator_fee = integrator::integrator_fee_amount(
+            &self.calculate_integrator_fee(integrator, amount_without_fee)
+        );
+        let net_amount = amount_without_fee - integrator_fee;
+
+        Ok(SwapAmounts {
+            amount_in: amount_in_with_fee,
+            gross_amount,
+            fee: gross_amount - amount_after_fee,
+            fee_rate,
+            fee_discount,
+            relayer_compensation,
+            integrator_fee,
+            net_amount,
+            net_amount_normalized: net_amount,
+        })
+    }
+
+    /// Amounts of swap to other `blockchain` by `account_id`, 
+    /// `transit_amount` is amount of `transfer_token` to be bridged. 
+    /// Fee is taken in target blockchain by `fee_rate` of account
+    /// reduced by `fee_discount` in basis points.
+    /// Used by `ft_on_transfer` and `quote_swap_to`.
+    pub(crate) fn calculate_swap_to(
+        &self,
+        blockchain: u64,
+        transit_amount: u128,
+        account_id: &AccountId,
+        fee_discount: u16,
+    ) -> SwapAmounts {
+        SwapAmounts {
+            amount_in: transit_amount,
+            gross_amount: transit_amount,
+            fee: 0,
+            fee_rate: self.discounted_fee_rate(account_id, transit_amount, fee_discount),
+            fee_discount,
+            relayer_compensation: 0,
+            integrator_fee: 0,
+            net_amount: transit_amount,
+            net_amount_normalized: self.normalize_amount_to(
+                blockchain,
+                transit_amount,
+                Rounding::Down,
+            ),
+        }
+    }
+
+    /// Amounts of swap of `amount` of `transfer_token` to other `blockchain`
+    /// without DEX swap. `integrator_fee` is taken from `amount` and 
+    /// the rest is bridged. Used by `ft_on_transfer` and `quote_swap_to`.
+    pub(crate) fn calculate_transfer_to(
+        &self,
+        blockchain: u64,
+        amount: u128,
+        account_id: &AccountId,
+        integrator_fee: &Option<IntegratorFee>,
+        fee_discount: u16,
+    ) -> SwapAmounts {
+        let integrator_fee = integrator::integrator_fee_amount(integrator_fee);
+
+        SwapAmounts {
+            amount_in: amount,
+            gross_amount: amount,
+            integrator_fee,
+            ..self.calculate_swap_to(blockchain, amount - integrator_fee, account_id, fee_discount)
+        }
+    }
+
+    pub(crate) fn to_swap_quote(&self, amounts: SwapAmounts) -> SwapQuote {
+        SwapQuote {
+            amount_in: U128(amounts.amount_in),
+            gross_amount: U128(amounts.gross_amount),
+            fee: U128(amounts.fee),
+            fee_rate: U128(amounts.fee_rate),
+            fee_discount: amounts.fee_discount,
+            relayer_compensation: U128(amounts.relayer_compensation),
+            integrator_fee: U128(amounts.integrator_fee),
+            net_amount: U128(amounts.net_amount),
+            net_amount_normalized: U128(amounts.net_amount_normalized),
+            is_amount_valid: 
+                self.check_amount_in(amounts.gross_amount).is_ok() &&
+                amounts.net_amount > 0,
+        }
+    }
+}
+
+/// Fee rate of the highest tier reached by `amount`, 
+/// `tiers` are sorted by `min_amount`
+fn tier_fee_rate(tiers: &[FeeTier], amount: u128) -> Option<u128> {
+    tiers
+        .iter()
+        .rev()
+        .find(|tier| amount >= u128::from(tier.min_amount))
+        .map(|tier| u128::from(tier.fee_amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiers(tiers: &[(u128, u128)]) -> Vec<FeeTier> {
+        tiers
+            .iter()
+            .map(|(min_amount, fee_amount)| FeeTier {
+                min_amount: U128(*min_amount),
+                fee_amount: U128(*fee_amount),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_tiers() {
+        assert_eq!(tier_fee_rate(&[], 1_000_000), None);
+    }
+
+    #[test]
+    fn amount_below_first_tier() {
+        let tiers = tiers(&[(1_000, 5_000), (10_000, 3_000)]);
+
+        assert_eq!(tier_fee_rate(&tiers, 999), None);
+    }
+
+    #[test]
+    fn highest_reached_tier() {
+        let tiers = tiers(&[(1_000, 5_000), (10_000, 3_000), (100_000, 1_000)]);
+
+        assert_eq!(tier_fee_rate(&tiers, 1_000), Some(5_000));
+        assert_eq!(tier_fee_rate(&tiers, 9_999), Some(5_000));
+        assert_eq!(tier_fee_rate(&tiers, 10_000), Some(3_000));
+        assert_eq!(tier_fee_rate(&tiers, 99_999), Some(3_000));
+        assert_eq!(tier_fee_rate(&tiers, u128::MAX), Some(1_000));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::interfaces::SwapRoute;
+
+use super::*;
+
+/// Max gas which can be attached to transaction
+pub const MAX_PREPAID_GAS: Gas = 300_000_000_000_000;
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            swap_base: 40_000_000_000_000,
+            swap_per_hop: 10_000_000_000_000,
+            ft_transfer: 10_000_000_000_000,
+            wrap_deposit: 10_000_000_000_000,
+            wrap_withdraw: 25_000_000_000_000,
+            callback: 45_000_000_000_000,
+            callback_payout: 3_000_000_000_000,
+            callback_swap_to: 100_000_000_000_000,
+            callback_split_swap_to: 130_000_000_000_000,
+            callback_split_swap_from: 140_000_000_000_000,
+            holder_balance_view: 10_000_000_000_000,
+            execution: 20_000_000_000_000,
+        }
+    }
+}
+
+impl GasConfig {
+    /// Gas for swap on DEX by `hops` swap actions
+    pub fn swap(&self, hops: usize) -> Gas {
+        self.swap_base + self.swap_per_hop * hops as Gas
+    }
+
+    /// Gas for swap by `actions` and `callback_after_swap_to`
+    pub fn swap_to(&self, actions: &[Action]) -> Gas {
+        self.swap(actions.len()) + self.callback_swap_to
+    }
+
+    /// Gas for `callback_after_near_deposit`, which swaps wrapped NEAR.
+    /// Swap checks that gas left covers `swap_to` and its own execution,
+    /// so callback gets execution budget on top of that.
+    pub fn callback_near_deposit(&self, actions: &[Action]) -> Gas {
+        self.execution * 2 + self.swap_to(actions)
+    }
+
+    /// Gas for swaps by all `routes`
+    pub fn split_swap(&self, routes: &[SwapRoute]) -> Gas {
+        routes
+            .iter()
+            .map(|route| self.swap(route.actions.len()))
+            .sum()
+    }
+
+    /// Gas for `callback_after_batch_transfer` settling `payouts`
+    pub fn callback_batch_transfer(&self, payouts: usize) -> Gas {
+        self.execution + self.callback_payout * payouts as Gas
+    }
+
+    /// Gas for payout of swap from other blockchain by `message`
+    pub fn swap_from(&self, message: &Option<SwapFromMessage>) -> Gas {
+        match message {
+            Some(SwapFromMessage::SplitSwap { routes }) => {
+                self.split_swap(routes) + self.callback_split_swap_from
+            }
+            Some(SwapFromMessage::RefFinance(RefFinanceReceiverMessage::ExecuteSwap {
+                actions, ..
+            })) => self.swap(actions.len()) + self.callback_split_swap_from,
+            None => self.ft_transfer + self.callback,
+        }
+    }
+}
+
+impl Contract {
+    /// Checks that gas left covers `required` gas of scheduled calls
+    /// and execution of called method
+    pub(crate) fn check_enough_gas(&self, required: Gas) -> Result<(), &'static str> {
+        let gas_left = env::prepaid_gas().saturating_sub(env::used_gas());
+        if gas_left < required + self.gas_config.execution {
+            return Err("Not enough gas attached");
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn assert_enough_gas(&self, required: Gas) {
+        if let Err(err) = self.check_enough_gas(required) {
+            env::panic(err.as_bytes());
+        }
+    }
+
+    pub(crate) fn validate_gas_config(&self) {
+        let gas_config = &self.gas_config;
+        assert!(
+            gas_config.swap_base > 0 &&
+            gas_config.ft_transfer > 0 &&
+            gas_config.wrap_deposit > 0 &&
+            gas_config.wrap_withdraw > 0 &&
+            gas_config.callback > 0 &&
+            gas_config.callback_payout > 0 &&
+            gas_config.callback_swap_to > 0 &&
+            gas_config.callback_split_swap_to > 0 &&
+            gas_config.callback_split_swap_from > 0 &&
+            gas_config.holder_balance_view > 0,
+            "Gas budgets must be positive"
+        );
+        assert!(
+            gas_config.execution +
+            gas_config.wrap_deposit +
+            gas_config.callback_near_deposit(&[]) <= MAX_PREPAID_GAS,
+            "Gas budgets of swap exceed max prepaid gas"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Number of payouts of batch which fit in max prepaid gas
+    fn batch_capacity(gas_config: &GasConfig, payout_gas: impl Fn(usize) -> Gas) -> usize {
+        (1..)
+            .take_while(|payouts| {
+                gas_config.execution + payout_gas(*payouts) <= MAX_PREPAID_GAS
+            })
+            .last()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn batch_transfers_fit() {
+        let gas_config = GasConfig::default();
+        let capacity = batch_capacity(&gas_config, |payouts| {
+            gas_config.ft_transfer * payouts as Gas +
+            gas_config.callback_batch_transfer(payouts)
+        });
+
+        assert!(capacity >= 20, "Only {} transfers fit", capacity);
+    }
+}
@@ -0,0 +1,130 @@
+use near_sdk::serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// Max fee of integrator, in hundredths of a bip (3%)
+pub const MAX_INTEGRATOR_FEE: u128 = 30_000;
+
+/// Integrator fee taken from swap to other blockchain,
+/// accrued after swap succeeded
+/// * `account_id` - account of integrator
+/// * `amount` - fee in `token_in`
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct IntegratorFee {
+    pub account_id: AccountId,
+    pub amount: U128,
+}
+
+#[ext_contract(ext_self_integrator)]
+pub trait AfterWithdrawIntegratorFee {
+    fn callback_after_withdraw_integrator_fee(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers fee of `token_id` accrued by caller as integrator
+    #[payable]
+    pub fn withdraw_integrator_fee(&mut self, token_id: ValidAccountId) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let token_id: AccountId = token_id.into();
+        let amount = self.integrator_fees
+            .remove(&(account_id.clone(), token_id.clone()))
+            .unwrap_or(0);
+        assert!(amount > 0, "No integrator fee to withdraw");
+        if token_id == self.transfer_token {
+            self.liquidity.fees -= amount;
+        }
+
+        ext_fungible_token::ft_transfer(
+            account_id.clone(),
+            U128(amount),
+            None,
+            &token_id,
+            1,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_integrator::callback_after_withdraw_integrator_fee(
+            account_id,
+            token_id,
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns integrator fee to balance if transfer failed
+    #[private]
+    pub fn callback_after_withdraw_integrator_fee(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "AfterWithdraw: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Withdrawal of integrator fee failed");
+            self.accrue_integrator_fee(&account_id, &token_id, u128::from(amount));
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn get_integrator_info(&self, integrator_id: &String) -> IntegratorInfo {
+        self.integrators
+            .get(integrator_id)
+            .expect("Unknown integrator")
+    }
+
+    /// Fee of `integrator` taken from `amount`, _None_ if swap has no integrator
+    pub(crate) fn calculate_integrator_fee(
+        &self,
+        integrator: &Option<String>,
+        amount: u128,
+    ) -> Option<IntegratorFee> {
+        integrator.as_ref().map(|integrator_id| {
+            let integrator = self.get_integrator_info(integrator_id);
+
+            IntegratorFee {
+                account_id: integrator.account_id,
+                amount: U128(amount * u128::from(integrator.fee) / FEE_DENOMINATOR),
+            }
+        })
+    }
+
+    pub(crate) fn accrue_integrator_fee(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &AccountId,
+        amount: u128,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        let key = (account_id.clone(), token_id.clone());
+        let balance = self.integrator_fees.get(&key).unwrap_or(0);
+        self.integrator_fees.insert(&key, &(balance + amount));
+
+        if *token_id == self.transfer_token {
+            self.liquidity.fees += amount;
+        }
+    }
+}
+
+/// Amount of optional integrator fee
+pub(crate) fn integrator_fee_amount(integrator_fee: &Option<IntegratorFee>) -> u128 {
+    integrator_fee
+        .as_ref()
+        .map_or(0, |integrator_fee| integrator_fee.amount.0)
+}
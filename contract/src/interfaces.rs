@@ -1,23 +1,162 @@
-use near_sdk::json_types::{U128, ValidAccountId};
+// List items in docs are aligned with descriptions of their items
+#![allow(clippy::doc_overindented_list_items)]
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64, ValidAccountId};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::AccountId;
+use near_sdk::{AccountId, Gas};
 
 /// Params required by cross-chain contract for swap from other blockchain
 /// * `new_address` - destination user address to transfer tokens
 /// * `token_out` - address of token that user wants to receive
-/// * `amount_in_without_fee - amount of tokens received in original blockchain
-///                             with substracted crypto_fee
+/// * `blockchain` - uuid of source blockchain
+/// * `amount_in_with_fee` - amount of tokens received in original blockchain
+///                          with substracted crypto_fee. Represented in 
+///                          decimals of transit token in source blockchain
 /// * `amount_out_min` - amount of tokens that user wants to receive 
 ///                         after swap 
 /// * `original_tx_hash` - original transactions hash from other blockchain
+/// * `dex` - id of registered DEX used for swap to `token_out`.
+///           If _None_ Ref Finance at `blockchain_router` is used
+/// * `referrer` - account which gets `referral_fee_share` of fee
+/// * `integrator` - id of registered integrator which takes its fee
+///                  in addition to fee of blockchain
+/// * `deadline` - timestamp in nanoseconds after which request is rejected
+/// * `swap_deadline` - timestamp in nanoseconds after which user gets
+///                     `transfer_token` instead of swap to `token_out`
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")] 
 pub struct SwapFromParams {
     pub new_address: ValidAccountId,
     pub token_out: ValidAccountId,
+    pub blockchain: u64,
     pub amount_in_with_fee: U128,
     pub amount_out_min: U128,
     pub original_tx_hash: String,
+    #[serde(default)]
+    pub dex: Option<String>,
+    #[serde(default)]
+    pub referrer: Option<ValidAccountId>,
+    #[serde(default)]
+    pub integrator: Option<String>,
+    #[serde(default)]
+    pub deadline: Option<U64>,
+    #[serde(default)]
+    pub swap_deadline: Option<U64>,
+}
+
+/// Tokens of swap to other blockchain held until bridged by relayer
+/// * `sender_id` - user who can refund tokens after timeout
+/// * `token_id` - `transfer_token` at time of swap
+/// * `amount` - transit amount
+/// * `created_at` - timestamp of swap in nanoseconds
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowRecord {
+    pub sender_id: AccountId,
+    pub token_id: AccountId,
+    pub amount: U128,
+    pub created_at: U64,
+}
+
+/// Withdrawal of liquidity provider waiting for available liquidity
+/// * `account_id` - liquidity provider
+/// * `amount` - amount of `transfer_token` to withdraw
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalRequest {
+    pub account_id: AccountId,
+    pub amount: U128,
+}
+
+/// Fee rate applied when amount reaches `min_amount`
+/// * `min_amount` - amount of swap or rolling volume of account 
+///                  in `transfer_token`
+/// * `fee_amount` - fee rate in hundredths of a bip, i.e. 1e-6
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeTier {
+    pub min_amount: U128,
+    pub fee_amount: U128,
+}
+
+/// Tiers of fee rate, each list is sorted by `min_amount`. 
+/// The lowest of `fee_amount_of_blockchain` and rates of reached
+/// tiers is applied.
+/// * `amount_tiers` - tiers by amount of single swap
+/// * `volume_tiers` - tiers by volume of account in last 30 days,
+///                    volume is not tracked if empty
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeSchedule {
+    pub amount_tiers: Vec<FeeTier>,
+    pub volume_tiers: Vec<FeeTier>,
+}
+
+/// Source of RBC balance of account for fee discount
+/// * `contract_id` - RBC token or staking contract
+/// * `method_name` - view method which takes `account_id` 
+///                   and returns balance as string, e.g. `ft_balance_of`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HolderBalanceSource {
+    pub contract_id: AccountId,
+    pub method_name: String,
+}
+
+/// Discount of fee applied when RBC balance reaches `min_balance`
+/// * `min_balance` - RBC balance of account
+/// * `discount` - discount in basis points of fee
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DiscountTier {
+    pub min_balance: U128,
+    pub discount: u16,
+}
+
+/// Compensation of relayer gas deducted from swaps from other blockchain
+/// * `Flat` - `amount` of `transfer_token`
+/// * `Gas` - `near_amount` of NEAR in yoctoNEAR priced 
+///           in `transfer_token` by `near_price`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RelayerCompensation {
+    Flat { amount: U128 },
+    Gas { near_amount: U128 },
+}
+
+/// Receiver of part of protocol fee
+/// * `account_id` - account which withdraws accrued fee
+/// * `share` - share of protocol fee in basis points
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeBeneficiary {
+    pub account_id: AccountId,
+    pub share: u16,
+}
+
+/// Record of audit log of fee distribution
+/// * `updated_by` - account which changed distribution
+/// * `updated_at` - timestamp of change in nanoseconds
+/// * `beneficiaries` - new table of fee beneficiaries
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeDistributionChange {
+    pub updated_by: AccountId,
+    pub updated_at: U64,
+    pub beneficiaries: Vec<FeeBeneficiary>,
+}
+
+/// Swap from other blockchain processed by `swap_tokens_to_user_batch`
+/// * `params` - struct SwapFromParams
+/// * `msg` - string with SwapFromMessage, the same as 
+///           in `swap_tokens_to_user_with_fee`
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapFromBatchItem {
+    pub params: SwapFromParams,
+    #[serde(default)]
+    pub msg: Option<String>,
 }
 
 /// Params required by cross-chain contract
@@ -25,12 +164,19 @@ pub struct SwapFromParams {
 ///                     First must be `transfer_token` in target blockchain.
 ///                     Last is `desired_token` in target blockchain.
 /// * `min_amount_out` - minimum amount of `desired_token` that user wants
-///                         to get in target blockchain
+///                         to get in target blockchain. Unsigned integer
+///                         in decimals of `desired_token`
 /// * `blockchain` - uuid of target blockchain
 /// * `new_address` - user's address in target blockchain
 /// * `swap_to_crypto` - _true_ if user wants to get crypto in target blockchain
 /// * `signature` - method signature of dex that will be used in target 
 ///                 blockchain for swaps 
+/// * `referrer` - referrer of swap. Fee of swap is taken in target 
+///                blockchain, so referrer is only passed in event
+/// * `integrator` - id of registered integrator which takes its fee
+///                  in `token_in`
+/// * `deadline` - timestamp in nanoseconds after which swap is rejected.
+///                Passed in event to be checked in target blockchain too
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct SwapToParams {
@@ -40,13 +186,178 @@ pub struct SwapToParams {
     pub new_address: String,
     pub swap_to_crypto: bool,
     pub signature: String,
+    #[serde(default)]
+    pub referrer: Option<ValidAccountId>,
+    #[serde(default)]
+    pub integrator: Option<String>,
+    #[serde(default)]
+    pub deadline: Option<U64>,
+}
+
+/// Address format used by other blockchain
+/// * `Evm` - `0x` prefixed hex, mixed case must match EIP-55 checksum
+/// * `Solana` - base58 encoded 32 bytes
+/// * `Tron` - base58check encoded address starting with 0x41
+/// * `Near` - NEAR account id
+/// * `Other` - any non-empty string
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AddressFormat {
+    Evm,
+    Solana,
+    Tron,
+    Near,
+    Other,
+}
+
+/// Registered other blockchain
+/// * `name` - human readable name of blockchain
+/// * `address_format` - format of user and token addresses
+/// * `chain_id` - chain id of blockchain if it has one (EVM chains)
+/// * `transit_token_decimals` - decimals of transit token in blockchain
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BlockchainInfo {
+    pub name: String,
+    pub address_format: AddressFormat,
+    pub chain_id: Option<u64>,
+    pub transit_token_decimals: u8,
+}
+
+/// Registered other blockchain with its Rubic address
+/// * `blockchain_num` - uuid of blockchain
+/// * `info` - blockchain metadata, flattened
+/// * `rubic_address` - Rubic address in blockchain if it was set
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BlockchainConfig {
+    pub blockchain_num: u64,
+    #[serde(flatten)]
+    pub info: BlockchainInfo,
+    pub rubic_address: Option<String>,
+}
+
+/// Interface implemented by DEX contract
+/// * `RefFinance` - Ref Finance and its forks, e.g. Jumbo
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DexKind {
+    RefFinance,
+}
+
+/// Registered DEX
+/// * `kind` - interface of DEX contract
+/// * `router` - DEX contract account
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DexInfo {
+    pub kind: DexKind,
+    pub router: AccountId,
+}
+
+/// Registered integrator
+/// * `account_id` - account which accrues and withdraws fee of integrator
+/// * `fee` - fee of integrator represented as hundredths of a bip, i.e. 1e-6
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct IntegratorInfo {
+    pub account_id: AccountId,
+    pub fee: U128,
+}
+
+/// Gas budgets of cross-contract calls
+/// * `swap_base` - gas for swap on DEX without hops, including
+///                 transfer of output back to this contract
+/// * `swap_per_hop` - gas added to `swap_base` for every swap action
+/// * `ft_transfer` - gas for `ft_transfer`
+/// * `wrap_deposit` - gas for `near_deposit` on wrap.near
+/// * `wrap_withdraw` - gas for `near_withdraw` on wrap.near
+/// * `callback` - gas for callbacks after transfers to user
+/// * `callback_payout` - gas added to `execution` by every payout
+///                       settled by callback after batch of transfers
+/// * `callback_swap_to` - gas for `callback_after_swap_to`
+/// * `callback_split_swap_to` - gas for `callback_after_split_swap_to`
+/// * `callback_split_swap_from` - gas for `callback_after_split_swap_from`
+/// * `holder_balance_view` - gas for view of RBC balance for fee discount
+/// * `execution` - gas reserved for execution of called method itself
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GasConfig {
+    pub swap_base: Gas,
+    pub swap_per_hop: Gas,
+    pub ft_transfer: Gas,
+    pub wrap_deposit: Gas,
+    pub wrap_withdraw: Gas,
+    pub callback: Gas,
+    pub callback_payout: Gas,
+    pub callback_swap_to: Gas,
+    pub callback_split_swap_to: Gas,
+    pub callback_split_swap_from: Gas,
+    pub holder_balance_view: Gas,
+    pub execution: Gas,
+}
+
+/// Configuration of cross-chain contract returned by `get_config`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractConfig {
+    pub version: u64,
+    pub owner: AccountId,
+    pub manager: AccountId,
+    pub relayer: AccountId,
+    pub transfer_token: AccountId,
+    pub transfer_token_decimals: u8,
+    pub blockchain_router: AccountId,
+    pub num_of_this_blockchain: u64,
+    pub min_token_amount: U128,
+    pub max_token_amount: U128,
+    pub fee_amount_of_blockchain: U128,
+    pub dex_referral_id: Option<AccountId>,
+    pub fee_treasury: Option<AccountId>,
+    pub referral_fee_share: u16,
+    pub lp_fee_share: u16,
+    pub gas_config: GasConfig,
+    pub escrow_timeout: U64,
+    pub is_swap_output_echo_enabled: bool,
+    pub is_running: bool,
+    pub is_upgrade_in_progress: bool,
+}
+
+/// Partial update of configuration applied by `update_config`.
+/// Fields set to _None_ are left unchanged.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigUpdate {
+    pub manager: Option<ValidAccountId>,
+    pub relayer: Option<ValidAccountId>,
+    pub transfer_token: Option<ValidAccountId>,
+    pub transfer_token_decimals: Option<u8>,
+    pub blockchain_router: Option<ValidAccountId>,
+    pub num_of_this_blockchain: Option<u64>,
+    pub min_token_amount: Option<U128>,
+    pub max_token_amount: Option<U128>,
+    pub fee_amount_of_blockchain: Option<U128>,
+    pub referral_fee_share: Option<u16>,
+    pub lp_fee_share: Option<u16>,
+    pub gas_config: Option<GasConfig>,
+    pub escrow_timeout: Option<U64>,
+    pub is_swap_output_echo_enabled: Option<bool>,
+    pub is_running: Option<bool>,
 }
 
 /// Message parameters to receive via token function call
 /// * `SwapTransferTokensToOther` - transfer tokens from user to pool 
 ///                                 and emit swapToOther event.
 /// * `SwapTokensToOther` - swap `token_in` for `transfer_token` via 
-///                         ref-finance and emit swapToOther evnet
+///                         `dex` and emit swapToOther evnet. If `dex` 
+///                         is _None_ Ref Finance at `blockchain_router` is used
+/// * `SplitSwapTokensToOther` - swap `token_in` for `transfer_token` by 
+///                              several parallel `routes` via `dex`. 
+///                              Outputs of routes must sum to `min_amount_out`
+/// * `ProvideLiquidity` - deposit `transfer_token` to liquidity pool
+///                        and mint LP shares to sender
+/// * `SwapOutput` - output of swap to other blockchain sent back by DEX,
+///                  accepted on pause as well
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub enum TokenReceiverMessage {
@@ -54,27 +365,75 @@ pub enum TokenReceiverMessage {
         swap_to_params: SwapToParams,
     },
     SwapTokensToOther {
-        swap_actions: Vec<SwapAction>,
+        swap_actions: Vec<Action>,
         swap_to_params: SwapToParams,
-    }
+        #[serde(default)]
+        dex: Option<String>,
+    },
+    SplitSwapTokensToOther {
+        routes: Vec<SwapRoute>,
+        min_amount_out: U128,
+        swap_to_params: SwapToParams,
+        #[serde(default)]
+        dex: Option<String>,
+    },
+    ProvideLiquidity,
+    SwapOutput {
+        min_amount_out: U128,
+    },
 }
 
-/// REF-FINANCE struct. Copypaste from https://github.com/ref-finance/ref-contracts/blob/audit_0.2.1/ref-exchange/src/token_receiver.rs
+/// One of parallel routes of split swap
+/// * `share` - share of amount in swapped by route, in basis points.
+///             Shares of all routes must sum to 10000
+/// * `actions` - swap actions of route. `amount_in` of the first action 
+///               is set by contract
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapRoute {
+    pub share: u16,
+    pub actions: Vec<Action>,
+}
+
+/// Message for swap from other blockchain to `token_out`
+/// * `SplitSwap` - swap `transfer_token` by several parallel routes
+/// * `RefFinance` - swap `transfer_token` by one route
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+pub enum SwapFromMessage {
+    SplitSwap {
+        routes: Vec<SwapRoute>,
+    },
+    RefFinance(RefFinanceReceiverMessage),
+}
+
+/// REF-FINANCE struct. Copypaste from https://github.com/ref-finance/ref-contracts/blob/main/ref-exchange/src/token_receiver.rs
 /// Message parametes to receive in ref-finance via token function call
-/// * 'ExecuteSwap' - alternative to deposit + execute ref-finance.SwapAction
+/// * 'ExecuteSwap' - alternative to deposit + execute ref-finance actions
+///     * `referral_id` - account that receives referral fee of ref-finance
+///     * `actions` - swap actions executed one by one
+///     * `client_echo` - if set, ref-finance sends output tokens to sender 
+///                       via `ft_transfer_call` with this msg
+///     * `skip_unwrap_near` - if not _true_, wrap.near output is unwrapped 
+///                            and sent as NEAR
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 #[serde(untagged)]
 pub enum RefFinanceReceiverMessage {
     ExecuteSwap {
-        referal_id: Option<ValidAccountId>,
-        force: u8,
+        referral_id: Option<ValidAccountId>,
         actions: Vec<Action>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_echo: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        skip_unwrap_near: Option<bool>,
     }
 }
 
-/// REF-FINANCE struct. Copypaste from https://github.com/ref-finance/ref-contracts/blob/audit_0.2.1/ref-exchange/src/action.rs
-/// Single swap action
+/// REF-FINANCE struct. Copypaste from https://github.com/ref-finance/ref-contracts/blob/main/ref-exchange/src/action.rs
+/// Single swap action. The same action is used for simple, 
+/// stable and rated pools, kind of pool is defined by `pool_id`.
 /// * `pool_id` - Pool which should be used for swapping
 /// * `token_in` - Token to swap from
 /// * `amount_in` - Amount to exchange.
@@ -93,7 +452,27 @@ pub struct SwapAction {
     pub min_amount_out: U128,
 }
 
-/// REF-FINANCE struct. Copypaste from https://github.com/ref-finance/ref-contracts/blob/audit_0.2.1/ref-exchange/src/action.rs
+/// REF-FINANCE struct. Copypaste from https://github.com/ref-finance/ref-contracts/blob/main/ref-exchange/src/action.rs
+/// Single swap action with exact amount out. Not supported by stable 
+/// and rated pools.
+/// * `pool_id` - Pool which should be used for swapping
+/// * `token_in` - Token to swap from
+/// * `amount_out` - Amount of token_out to receive.
+///                  If amount_out is None, it will take amount_in 
+///                  of next step.
+/// * `token_out` - Token to swap into
+/// * `max_amount_in` - Maximum amount of token_in to spend
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapByOutputAction {
+    pub pool_id: u64,
+    pub token_in: AccountId,
+    pub amount_out: Option<U128>,
+    pub token_out: AccountId,
+    pub max_amount_in: Option<U128>,
+}
+
+/// REF-FINANCE struct. Copypaste from https://github.com/ref-finance/ref-contracts/blob/main/ref-exchange/src/action.rs
 /// Single action. 
 /// Allows to execute sequence of various actions initiated by an account.
 #[derive(Serialize, Deserialize)]
@@ -101,4 +480,39 @@ pub struct SwapAction {
 #[serde(untagged)]
 pub enum Action {
     Swap(SwapAction),
+    SwapByOutput(SwapByOutputAction),
+}
+
+impl Action {
+    pub fn token_in(&self) -> &AccountId {
+        match self {
+            Action::Swap(swap_action) => &swap_action.token_in,
+            Action::SwapByOutput(swap_action) => &swap_action.token_in,
+        }
+    }
+
+    pub fn token_out(&self) -> &AccountId {
+        match self {
+            Action::Swap(swap_action) => &swap_action.token_out,
+            Action::SwapByOutput(swap_action) => &swap_action.token_out,
+        }
+    }
+
+    /// Amount of `token_out` guaranteed by action: `min_amount_out` of
+    /// swap or `amount_out` of swap by output
+    pub fn min_amount_out(&self) -> Option<U128> {
+        match self {
+            Action::Swap(swap_action) => Some(swap_action.min_amount_out),
+            Action::SwapByOutput(swap_action) => swap_action.amount_out,
+        }
+    }
+
+    /// Sets amount of `token_in` to spend: `amount_in` of swap 
+    /// or `max_amount_in` of swap by output
+    pub fn set_amount_in(&mut self, amount_in: U128) {
+        match self {
+            Action::Swap(swap_action) => swap_action.amount_in = Some(amount_in),
+            Action::SwapByOutput(swap_action) => swap_action.max_amount_in = Some(amount_in),
+        }
+    }
 }
\ No newline at end of file
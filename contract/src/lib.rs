@@ -1,9 +1,14 @@
+// Methods of ext_contract and near_bindgen code generated for callbacks
+// take all swap params, attributes on them are not passed to generated code
+#![allow(clippy::too_many_arguments)]
+
 use std::convert::TryInto;
 
+use near_contract_standards::fungible_token::FungibleToken;
 use near_contract_standards::fungible_token::core_impl::ext_fungible_token;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::json_types::{U128, ValidAccountId};
-use near_sdk::collections::{LookupMap, LookupSet};
+use near_sdk::json_types::{U128, U64, ValidAccountId};
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, UnorderedMap, Vector};
 use near_sdk::{
     env, near_bindgen, ext_contract, Promise,
     AccountId, Gas, PromiseResult,
@@ -11,47 +16,60 @@ use near_sdk::{
     serde_json, 
 };
 pub use crate::interfaces::{
-    Action, SwapAction, RefFinanceReceiverMessage, SwapFromParams,
+    Action, SwapAction, SwapByOutputAction, RefFinanceReceiverMessage, SwapFromParams,
+    SwapFromMessage,
+    SwapToParams, BlockchainInfo, BlockchainConfig,
+    ContractConfig, ConfigUpdate, DexInfo, IntegratorInfo, GasConfig, EscrowRecord,
+    WithdrawalRequest, FeeBeneficiary, FeeDistributionChange, FeeTier, FeeSchedule,
+    HolderBalanceSource, DiscountTier, RelayerCompensation,
 };
+use crate::events::{Event, SwapFromEvent, SwapFromFailedEvent, SwapToEvent};
+use crate::normalization::Rounding;
+use crate::fees::{SwapQuote, FEE_DENOMINATOR, BASIS_POINTS_DENOMINATOR};
+use crate::integrator::{IntegratorFee, MAX_INTEGRATOR_FEE};
+use crate::liquidity::{Liquidity, LiquidityInfo};
+use crate::lp::LpPoolInfo;
+use crate::volume::VolumeBucket;
 
 mod token_receiver;
 mod views;
 mod management;
 mod interfaces;
 mod utils;
+mod address;
+mod normalization;
+mod events;
+mod fees;
+mod dex;
+mod split;
+mod referral;
+mod integrator;
+mod native;
+mod upgrade;
+mod gas;
+mod batch;
+mod escrow;
+mod liquidity;
+mod lp;
+mod fee_split;
+mod fee_conversion;
+mod volume;
+mod discount;
+mod relayer;
+mod payout;
 
-pub const GAS_FOR_FT_TRANSFER_CALL_SWAP_TO: Gas = 90_000_000_000_000;
-pub const GAS_FOT_FT_TRANSFER_CALL: Gas = 35_000_000_000_000;
-pub const GAS_FOR_FT_TRANSFER: Gas =      30_000_000_000_000;
-pub const GAS_FOR_CALLBACK_SWAP_TO: Gas = 120_000_000_000_000;
-pub const GAS_FOR_CALLBACK: Gas =         45_000_000_000_000;
-pub const GAS_FOR_SWAP: Gas =             30_000_000_000_000;
-pub const GAS_FOR_WITHDRAW: Gas =         60_000_000_000_000;
-pub const GAS_FOR_WRAP_WITHDRAW: Gas =    50_000_000_000_000;
+pub const GAS_FOR_DEPLOY_UPGRADE: Gas =   50_000_000_000_000;
+pub const GAS_FOR_MIGRATE: Gas =          120_000_000_000_000;
 
 pub const WRAP_NEAR: &str = "wrap.near";
 
-#[ext_contract(ext_ref)]
-pub trait ExtRefFinanceContract {
-    fn swap(
-        &mut self,
-        actions: Vec<SwapAction>,
-        referral_id: Option<ValidAccountId>,
-    ) -> U128;
-    fn withdraw(
-        &mut self,
-        token_id: ValidAccountId,
-        amount: U128,
-        unregister: Option<bool>,
-    ) -> Promise;
-}
-
 #[ext_contract(ext_wrap)]
 pub trait ExtWrapContarct {
     fn near_withdraw(
         &mut self,
         amount: U128,
     ) -> Promise;
+    fn near_deposit(&mut self);
 }
 
 #[ext_contract(ext_self)]
@@ -62,13 +80,33 @@ pub trait AfterSwap {
         token_in: AccountId,
         amount_in: U128,
         min_amount_out: U128,
-    ) -> Promise;
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+        refund_in_near: bool,
+    );
     fn callback_after_swap_from(
         &mut self,
-        original_tx_hash: String,
-        new_address_for_near_receive: Option<ValidAccountId>,
-        min_amount_out: Option<U128>,
+        swap_from_event: SwapFromEvent,
+        transit_amount: U128,
+        amount_out: U128,
     );
+    fn callback_after_split_swap_to(
+        &mut self,
+        sender_id: AccountId,
+        token_in: AccountId,
+        amount_in: U128,
+        route_amounts: Vec<U128>,
+        route_amounts_out: Vec<U128>,
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+    );
+    fn callback_after_split_swap_from(
+        &mut self,
+        swap_from_event: SwapFromEvent,
+        new_address: ValidAccountId,
+        route_amounts: Vec<U128>,
+        route_amounts_out: Vec<U128>,
+    ) -> Promise;
 }
 
 pub trait AfterSwap {
@@ -78,21 +116,58 @@ pub trait AfterSwap {
         token_in: AccountId,
         amount_in: U128,
         min_amount_out: U128,
-    ) -> Promise;
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+        refund_in_near: bool,
+    );
     fn callback_after_swap_from(
         &mut self,
-        original_tx_hash: String,
-        new_address_for_near_receive: Option<ValidAccountId>,
-        min_amount_out: Option<U128>,
+        swap_from_event: SwapFromEvent,
+        transit_amount: U128,
+        amount_out: U128,
     );
+    fn callback_after_split_swap_to(
+        &mut self,
+        sender_id: AccountId,
+        token_in: AccountId,
+        amount_in: U128,
+        route_amounts: Vec<U128>,
+        route_amounts_out: Vec<U128>,
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+    );
+    fn callback_after_split_swap_from(
+        &mut self,
+        swap_from_event: SwapFromEvent,
+        new_address: ValidAccountId,
+        route_amounts: Vec<U128>,
+        route_amounts_out: Vec<U128>,
+    ) -> Promise;
 }
 
 #[derive(BorshStorageKey, BorshSerialize)]
 pub(crate) enum StorageKey {
-    RbcAddresses,
-    ExistingOther,
+    #[allow(dead_code)]
+    RbcAddresses, // unused, keeps prefixes of next keys
+    #[allow(dead_code)]
+    ExistingOther, // unused, keeps prefixes of next keys
     CryptoFee,
     ProcessedTx,
+    StagedCode,
+    OtherBlockchains,
+    RubicAddresses,
+    Dexes,
+    ReferralBalances,
+    Integrators,
+    IntegratorFees,
+    Escrows,
+    LpShares,
+    WithdrawalQueue,
+    BeneficiaryFees,
+    FeeDistributionHistory,
+    AccountVolumes,
+    RelayerCompensations,
+    PendingPayouts,
 }
 
 #[near_bindgen]
@@ -103,16 +178,66 @@ pub struct Contract {
     relayer: AccountId,
     transfer_token: AccountId,
     blockchain_router: AccountId,
+    transfer_token_decimals: u8,
     num_of_this_blockchain: u64,
     min_token_amount: u128,
     max_token_amount: u128,
     acc_token_fee: u128,
     fee_amount_of_blockchain: u128,
-    rubic_addresses: LookupMap<u64, String>,
-    existing_other_blockchain: LookupSet<u64>,
+    rubic_addresses: UnorderedMap<u64, String>,
+    other_blockchains: UnorderedMap<u64, BlockchainInfo>,
+    dexes: UnorderedMap<String, DexInfo>,
+    dex_referral_id: Option<AccountId>,
+    referral_fee_share: u16,
+    referral_balances: LookupMap<AccountId, u128>,
+    integrators: UnorderedMap<String, IntegratorInfo>,
+    /// Fees accrued by integrator accounts per token
+    integrator_fees: LookupMap<(AccountId, AccountId), u128>,
+    gas_config: GasConfig,
+    escrows: LookupMap<u64, EscrowRecord>,
+    next_escrow_id: u64,
+    /// Time in nanoseconds after which user can refund not bridged tokens
+    escrow_timeout: u64,
+    liquidity: Liquidity,
+    /// Output of swaps to other blockchain is returned by DEX with
+    /// `client_echo`, so surplus over min amount out is recorded
+    is_swap_output_echo_enabled: bool,
+    /// NEP-141 shares of liquidity providers
+    lp_token: FungibleToken,
+    /// `transfer_token` owned by liquidity providers
+    lp_liquidity: u128,
+    lp_fee_share: u16,
+    withdrawal_queue: Vector<WithdrawalRequest>,
+    /// Index of the first not processed request in `withdrawal_queue`
+    withdrawal_queue_head: u64,
+    queued_withdrawals: u128,
+    /// Distribution of protocol fee, empty if fee goes to `acc_token_fee`
+    fee_beneficiaries: Vec<FeeBeneficiary>,
+    beneficiary_fees: LookupMap<AccountId, u128>,
+    /// Audit log of changes of `fee_beneficiaries`
+    fee_distribution_history: Vector<FeeDistributionChange>,
+    /// Receiver of NEAR from conversion of accrued fee
+    fee_treasury: Option<AccountId>,
+    fee_schedule: FeeSchedule,
+    /// Daily volumes of accounts in `transfer_token` for volume fee tiers
+    account_volumes: LookupMap<AccountId, Vec<VolumeBucket>>,
+    /// RBC balance of receiver gives discount of fee by `discount_tiers`
+    holder_balance_source: Option<HolderBalanceSource>,
+    discount_tiers: Vec<DiscountTier>,
+    /// Compensation of relayer gas per source blockchain
+    relayer_compensations: LookupMap<u64, RelayerCompensation>,
+    /// Number of blockchains with compensation of gas
+    gas_compensations_count: u64,
+    /// Amount of `transfer_token` per 1 NEAR
+    near_price: u128,
+    relayer_compensation_balance: u128,
+    /// Payouts to users per token which failed and can be claimed
+    pending_payouts: LookupMap<(AccountId, AccountId), u128>,
     blockchain_crypto_fee: LookupMap<u64, U128>, // unused
     processed_tx: LookupSet<String>,
     is_running: bool,
+    staged_code: LazyOption<Vec<u8>>,
+    running_before_upgrade: Option<bool>,
 }
 
 #[near_bindgen]
@@ -124,34 +249,73 @@ impl Contract {
         relayer_id: ValidAccountId,
         transfer_token: ValidAccountId,
         blockchain_router: ValidAccountId,
+        transfer_token_decimals: u8,
         num_of_this_blockchain: u64,
         min_token_amount: U128,
         max_token_amount: U128,
         fee_amount_of_blockchain: U128,
         is_running: bool,
     ) -> Self {
-        Self {
+        let contract = Self {
             owner: owner_id.as_ref().clone(),
             manager: manager_id.as_ref().clone(),
             relayer: relayer_id.as_ref().clone(),
             transfer_token: transfer_token.as_ref().clone(),
             blockchain_router: blockchain_router.as_ref().clone(),
+            transfer_token_decimals,
             num_of_this_blockchain,
             min_token_amount: u128::from(min_token_amount),
             max_token_amount: u128::from(max_token_amount),
             fee_amount_of_blockchain: u128::from(fee_amount_of_blockchain),
             acc_token_fee: 0,
-            rubic_addresses: LookupMap::new(StorageKey::RbcAddresses),
-            existing_other_blockchain: LookupSet::new(StorageKey::ExistingOther),
+            rubic_addresses: UnorderedMap::new(StorageKey::RubicAddresses),
+            other_blockchains: UnorderedMap::new(StorageKey::OtherBlockchains),
+            dexes: UnorderedMap::new(StorageKey::Dexes),
+            dex_referral_id: None,
+            referral_fee_share: 0,
+            referral_balances: LookupMap::new(StorageKey::ReferralBalances),
+            integrators: UnorderedMap::new(StorageKey::Integrators),
+            integrator_fees: LookupMap::new(StorageKey::IntegratorFees),
+            gas_config: GasConfig::default(),
+            escrows: LookupMap::new(StorageKey::Escrows),
+            next_escrow_id: 0,
+            escrow_timeout: escrow::DEFAULT_ESCROW_TIMEOUT,
+            liquidity: Liquidity::default(),
+            is_swap_output_echo_enabled: false,
+            lp_token: FungibleToken::new(StorageKey::LpShares),
+            lp_liquidity: 0,
+            lp_fee_share: 0,
+            withdrawal_queue: Vector::new(StorageKey::WithdrawalQueue),
+            withdrawal_queue_head: 0,
+            queued_withdrawals: 0,
+            fee_beneficiaries: Vec::new(),
+            beneficiary_fees: LookupMap::new(StorageKey::BeneficiaryFees),
+            fee_distribution_history: Vector::new(StorageKey::FeeDistributionHistory),
+            fee_treasury: None,
+            fee_schedule: FeeSchedule::default(),
+            account_volumes: LookupMap::new(StorageKey::AccountVolumes),
+            holder_balance_source: None,
+            discount_tiers: Vec::new(),
+            relayer_compensations: LookupMap::new(StorageKey::RelayerCompensations),
+            gas_compensations_count: 0,
+            near_price: 0,
+            relayer_compensation_balance: 0,
+            pending_payouts: LookupMap::new(StorageKey::PendingPayouts),
             blockchain_crypto_fee: LookupMap::new(StorageKey::CryptoFee),
             processed_tx: LookupSet::new(StorageKey::ProcessedTx),
             is_running,
-        }
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            running_before_upgrade: None,
+        };
+        contract.validate_config();
+
+        contract
     }
 
     /// Transfer tokens to end user in current blockchain
     /// * `params` - struct SwapFromParams
-    /// * `msg` - string with RefFinanceReceiverMessage. 
+    /// * `msg` - string with SwapFromMessage, i.e. RefFinanceReceiverMessage 
+    ///           or split swap routes. 
     ///             If _None_ that user will get `transfer_token`         
     #[payable]
     pub fn swap_tokens_to_user_with_fee(
@@ -161,116 +325,70 @@ impl Contract {
     ) -> Promise {
         self.assert_contract_running();
         self.assert_relayer();
-        self.validate_swap_from(&params);
 
-        assert_eq!(
-            self.processed_tx.contains(&params.original_tx_hash),
-            false,
-            "Swap already processed",
-        );
+        let message = self.check_swap_from(&params, &msg)
+            .unwrap_or_else(|err| env::panic(err.as_bytes()));
+        self.assert_enough_gas(self.swap_from_gas(&message));
+
+        self.dispatch_swap_tokens_to_user(params, msg, message)
+    }
+}
 
-        let amount_in_without_fee = 
-            u128::from(params.amount_in_with_fee) *  
-            (1_000_000 - self.fee_amount_of_blockchain) / 
-            1_000_000;
-
-        self.acc_token_fee += 
-            u128::from(params.amount_in_with_fee) - 
-            amount_in_without_fee; 
-
-        match msg {
-            Some(ref_finance_receiver_msg) => {
-                let msg = serde_json::from_str::<RefFinanceReceiverMessage>(&ref_finance_receiver_msg)
-                    .and_then(|message|{
-                        let msg_without_fee = match message {
-                            RefFinanceReceiverMessage::ExecuteSwap { 
-                                referal_id, force, mut actions 
-                            } => {
-                                assert!(actions.len() > 0, "Must be 1 or more SwapAction in msg");
-                                let action = match actions.remove(0) {
-                                    Action::Swap(swap_action) => {
-                                        let mut swap_action_without_fee = swap_action;
-                                        swap_action_without_fee.amount_in = Some(U128(amount_in_without_fee));
-
-                                        Action::Swap(swap_action_without_fee)
-                                    }
-                                };
-
-                                actions.push(action);
-
-                                RefFinanceReceiverMessage::ExecuteSwap{
-                                    referal_id, force, actions,
-                                }
-                            }
-                        };
-
-                        serde_json::to_string(&msg_without_fee)
-                    })
-                    .unwrap();
-
-                match params.token_out.to_string().as_str() {
-                    WRAP_NEAR => {
-                        // Transfer `transfer_token` to REF-FINANCE and swap them 
-                        // for `desired token`.
-                        ext_fungible_token::ft_transfer_call(
-                            self.get_blockchain_router(),
-                            U128(amount_in_without_fee),
-                            None,
-                            msg,
-                            &self.transfer_token,
-                            1,
-                            GAS_FOR_FT_TRANSFER_CALL_SWAP_TO,
-                        )
-                        // Withdraw wrap.near 
-                        // If swap on previous tx will fail, than 
-                        // `promise_after_transfer_call` will refund to this contract.
-                        // This promise also will fail.
-                        .then(ext_wrap::near_withdraw(
-                            params.amount_out_min,
-                            &WRAP_NEAR.to_string(),
-                            1,
-                            GAS_FOR_WRAP_WITHDRAW,
-                        ))
-                        .then(ext_self::callback_after_swap_from(
-                            params.original_tx_hash,
-                            Some(params.new_address),
-                            Some(params.amount_out_min),
-                            &env::current_account_id(),
-                            0,
-                            GAS_FOR_CALLBACK,
-                        ))
-                    },
-                    _ => {
-                        // Transfer `transfer_token` to REF-FINANCE and swap them 
-                        // for `desired token`.
-                        ext_fungible_token::ft_transfer_call(
-                            self.get_blockchain_router(),
-                            U128(amount_in_without_fee),
-                            None,
-                            msg,
-                            &self.transfer_token,
-                            1,
-                            GAS_FOR_FT_TRANSFER_CALL_SWAP_TO,
-                        )
-                        // Transfer `token_out` tokens to the `new_address`.
-                        .then(ext_fungible_token::ft_transfer(
-                            params.new_address.to_string(),
-                            params.amount_out_min,
-                            None,
-                            &params.token_out.to_string(),
-                            1,
-                            GAS_FOR_FT_TRANSFER,
-                        ))
-                        .then(ext_self::callback_after_swap_from(
-                            params.original_tx_hash,
-                            None, 
-                            None,
-                            &env::current_account_id(),
-                            0,
-                            GAS_FOR_CALLBACK,
-                        ))
+impl Contract {
+    /// Reserves payout with fee reduced by `fee_discount` in basis points 
+    /// and transfers tokens to user by checked `message`. 
+    /// Fees are accrued by `settle_swap_from` after payout.
+    pub(crate) fn internal_swap_tokens_to_user(
+        &mut self,
+        params: SwapFromParams,
+        message: Option<SwapFromMessage>,
+        fee_discount: u16,
+    ) -> Promise {
+        let (swap_from_event, amount_in_without_fee) = 
+            self.reserve_swap_from(&params, &message, fee_discount);
+
+        match message {
+            Some(message) => {
+                let mut actions = match message {
+                    SwapFromMessage::SplitSwap { routes } => {
+                        return self.split_swap_tokens_to_user(
+                            params,
+                            amount_in_without_fee,
+                            routes,
+                            swap_from_event,
+                        );
                     }
-                }
+                    SwapFromMessage::RefFinance(RefFinanceReceiverMessage::ExecuteSwap { 
+                        actions, ..
+                    }) => actions,
+                };
+
+                // Only the first hop takes `transfer_token`, 
+                // order of hops is kept
+                actions[0].set_amount_in(U128(amount_in_without_fee));
+
+                let dex = self.get_dex_adapter(&params.dex);
+                let swap_gas = self.gas_config.swap(actions.len());
+
+                // Transfer `transfer_token` to DEX and swap them 
+                // for `desired token`. Result of swap is checked by
+                // callback before payout, as for single route split swap.
+                dex.instant_swap(
+                    &self.transfer_token,
+                    U128(amount_in_without_fee),
+                    actions,
+                    None,
+                    swap_gas,
+                )
+                .then(ext_self::callback_after_split_swap_from(
+                    swap_from_event,
+                    params.new_address,
+                    vec![U128(amount_in_without_fee)],
+                    vec![params.amount_out_min],
+                    &env::current_account_id(),
+                    0,
+                    self.gas_config.callback_split_swap_from,
+                ))
             },
             None => {
                 ext_fungible_token::ft_transfer(
@@ -279,19 +397,191 @@ impl Contract {
                     None,
                     &self.transfer_token,
                     1,
-                    GAS_FOR_FT_TRANSFER,
+                    self.gas_config.ft_transfer,
                 )
                 .then(ext_self::callback_after_swap_from(
-                    params.original_tx_hash,
-                    None,
-                    None,
+                    swap_from_event,
+                    U128(amount_in_without_fee),
+                    U128(0),
                     &env::current_account_id(),
                     0,
-                    GAS_FOR_CALLBACK,
+                    self.gas_config.callback,
                 ))
             }
         }
     }
+
+    /// Reserves payout of checked swap from other blockchain with fee 
+    /// reduced by `fee_discount` in basis points. Returns event of swap 
+    /// to be emitted when it is settled and amount to be paid to user.
+    pub(crate) fn reserve_swap_from(
+        &mut self,
+        params: &SwapFromParams,
+        message: &Option<SwapFromMessage>,
+        fee_discount: u16,
+    ) -> (SwapFromEvent, u128) {
+        // `amount_in_with_fee` is represented in decimals of
+        // transit token in source blockchain
+        let amounts = self.calculate_swap_from(
+            params.blockchain,
+            u128::from(params.amount_in_with_fee),
+            params.new_address.as_ref(),
+            &params.integrator,
+            fee_discount,
+        ).unwrap_or_else(|err| env::panic(err.as_bytes()));
+        self.reserve_payout(amounts.gross_amount);
+
+        let swap_from_event = SwapFromEvent {
+            original_tx_hash: params.original_tx_hash.clone(),
+            blockchain: params.blockchain,
+            new_address: params.new_address.to_string(),
+            // Without message user gets `transfer_token`, 
+            // e.g. after swap deadline
+            token_out: match message {
+                Some(_) => params.token_out.to_string(),
+                None => self.transfer_token.clone(),
+            },
+            amount_in_with_fee: params.amount_in_with_fee,
+            amount_in_with_fee_normalized: U128(amounts.gross_amount),
+            fee: U128(amounts.fee),
+            fee_discount: amounts.fee_discount,
+            relayer_compensation: U128(amounts.relayer_compensation),
+            integrator: params.integrator
+                .as_ref()
+                .map(|integrator_id| self.get_integrator_info(integrator_id).account_id),
+            integrator_fee: U128(amounts.integrator_fee),
+            amount_out_min: params.amount_out_min,
+            referrer: params.referrer.as_ref().map(|referrer| referrer.to_string()),
+            // Parts of fee are set when swap is settled
+            referral_fee: U128(0),
+            lp_fee: U128(0),
+        };
+
+        (swap_from_event, amounts.net_amount)
+    }
+
+    /// Settles swap from other blockchain by results of payouts of 
+    /// `transit_amount` of `transfer_token` and `amount_out` of `token_out`.
+    /// If nothing was paid and no swap was made, payout is released and 
+    /// swap can be retried. Otherwise fees are accrued and failed payouts 
+    /// can be claimed by user.
+    pub(crate) fn complete_swap_from(
+        &mut self,
+        swap_from_event: SwapFromEvent,
+        transit_amount: u128,
+        is_transit_paid: bool,
+        amount_out: u128,
+        is_out_paid: bool,
+    ) {
+        let mut swap_from_event = swap_from_event;
+        let new_address = swap_from_event.new_address.clone();
+        let gross_amount = u128::from(swap_from_event.amount_in_with_fee_normalized);
+
+        if amount_out == 0 && !is_transit_paid {
+            self.release_payout(gross_amount, false);
+
+            Event::SwapFromOtherBlockchainFailed(&SwapFromFailedEvent {
+                original_tx_hash: swap_from_event.original_tx_hash,
+                reason: "Transfer to user failed".to_string(),
+            }).emit();
+            return;
+        }
+
+        self.release_payout(gross_amount, true);
+        self.settle_swap_from(&mut swap_from_event);
+
+        if !is_transit_paid {
+            env::log(b"Transfer of transit tokens failed, they can be claimed");
+            self.credit_pending_payout(
+                &new_address,
+                &self.transfer_token.clone(),
+                transit_amount,
+            );
+        }
+        if !is_out_paid {
+            env::log(b"Transfer of token out failed, it can be claimed");
+            self.credit_pending_payout(
+                &new_address,
+                &swap_from_event.token_out,
+                amount_out,
+            );
+        } else if amount_out > 0 && swap_from_event.token_out == WRAP_NEAR {
+            // Transfer unwrapped NEAR to `new_address`
+            Promise::new(new_address).transfer(amount_out);
+        }
+
+        Event::SwapFromOtherBlockchain(&swap_from_event).emit();
+        self.processed_tx.insert(&swap_from_event.original_tx_hash);
+    }
+
+    /// Completes swap to other blockchain of `amount_in` of `token_in`:
+    /// integrator fee is accrued, `transit_amount` is held in escrow
+    /// and SwapToOtherBlockchain event is emitted with fee rate reduced 
+    /// by `fee_discount`
+    pub(crate) fn finalize_swap_to(
+        &mut self,
+        sender_id: &AccountId,
+        token_in: &AccountId,
+        amount_in: u128,
+        transit_amount: u128,
+        swap_to_params: SwapToParams,
+        integrator_fee: &Option<IntegratorFee>,
+        fee_discount: u16,
+    ) {
+        let fee_amount = integrator::integrator_fee_amount(integrator_fee);
+        if let Some(integrator_fee) = integrator_fee {
+            self.accrue_integrator_fee(&integrator_fee.account_id, token_in, fee_amount);
+        }
+
+        let amounts = self.calculate_swap_to(
+            swap_to_params.blockchain,
+            transit_amount,
+            sender_id,
+            fee_discount,
+        );
+        let escrow_id = self.create_escrow(sender_id, amounts.net_amount);
+        self.record_volume(sender_id, amounts.gross_amount);
+
+        Event::SwapToOtherBlockchain(&SwapToEvent {
+            escrow_id,
+            sender_id: sender_id.clone(),
+            token_in: token_in.clone(),
+            amount_in: U128(amount_in),
+            integrator_fee: U128(fee_amount),
+            fee_rate: U128(amounts.fee_rate),
+            fee_discount: amounts.fee_discount,
+            transit_amount: U128(amounts.net_amount),
+            transit_amount_normalized: U128(amounts.net_amount_normalized),
+            swap_to_params,
+        }).emit();
+    }
+
+    /// Accrues fees of swap from other blockchain when it is paid to user:
+    /// protocol fee with referral and LP parts, relayer compensation and
+    /// integrator fee. Volume of receiver is recorded as well.
+    fn settle_swap_from(&mut self, swap_from_event: &mut SwapFromEvent) {
+        let fee = u128::from(swap_from_event.fee);
+        self.liquidity.fees += fee;
+        let referral_fee = self.credit_referral_fee(&swap_from_event.referrer, fee);
+        let lp_fee = self.credit_lp_fee(fee - referral_fee);
+        self.accrue_protocol_fee(fee - referral_fee - lp_fee);
+        self.accrue_relayer_compensation(swap_from_event.relayer_compensation.into());
+
+        if let Some(integrator_id) = &swap_from_event.integrator {
+            self.accrue_integrator_fee(
+                integrator_id,
+                &self.transfer_token.clone(),
+                swap_from_event.integrator_fee.into(),
+            );
+        }
+        self.record_volume(
+            &swap_from_event.new_address,
+            swap_from_event.amount_in_with_fee_normalized.into(),
+        );
+
+        swap_from_event.referral_fee = U128(referral_fee);
+        swap_from_event.lp_fee = U128(lp_fee);
+    }
 }
 
 #[near_bindgen]
@@ -303,85 +593,199 @@ impl AfterSwap for Contract {
         token_in: AccountId,
         amount_in: U128,
         min_amount_out: U128,
-    ) -> Promise {
-        assert_eq!(env::promise_results_count(), 1, "AfterSwap: Expected 1 promise result");
-        
-        match env::promise_result(0) {
-            PromiseResult::Failed => {
-                env::log(b"Swap failed");
-
-                ext_ref::withdraw(
-                    token_in.clone().try_into().unwrap(),
-                    amount_in,
-                    None,
-                    &self.blockchain_router,
-                    1,
-                    GAS_FOR_WITHDRAW,
-                )
-                .then(ext_fungible_token::ft_transfer(
-                    sender_id,
-                    amount_in,
-                    None,
-                    &token_in,
-                    1,
-                    GAS_FOT_FT_TRANSFER_CALL,
-                ))
-            }
-            PromiseResult::Successful(_) => {
-                env::log(b"SwapToOtherBlockchain");
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+        refund_in_near: bool,
+    ) {
+        let fee_amount = integrator::integrator_fee_amount(&integrator_fee);
+        // Result of instant swap is the amount used by DEX, 
+        // unused tokens are already returned to this contract
+        let result = split::collect_split_swap_results(
+            &[U128(u128::from(amount_in) - fee_amount)],
+            &[min_amount_out],
+        );
+        let mut amount_returned = result.amount_returned;
 
-                ext_ref::withdraw(
-                    self.get_transfer_token().try_into().unwrap(),
-                    min_amount_out,
-                    None,
-                    &self.blockchain_router,
-                    1,
-                    GAS_FOR_WITHDRAW,
-                )
-            }
-            PromiseResult::NotReady => {
-                unreachable!()
-            }
+        if result.failed_routes > 0 {
+            env::log(b"Swap failed");
+
+            // Integrator fee is not taken from failed swap
+            amount_returned += fee_amount;
+        } else {
+            // RBC balance of sender is requested in parallel with swap
+            let fee_discount = self.holder_fee_discount_of_result(1);
+
+            self.finalize_swap_to(
+                &sender_id,
+                &token_in,
+                u128::from(amount_in) - amount_returned,
+                u128::from(min_amount_out),
+                swap_to_params,
+                &integrator_fee,
+                fee_discount,
+            );
+        }
+
+        if amount_returned > 0 {
+            self.refund(sender_id, vec![token_in], vec![U128(amount_returned)], refund_in_near);
         }
     }
 
+    /// Settles swap from other blockchain after payouts of `transit_amount`
+    /// of `transfer_token` and `amount_out` of `token_out`, each is
+    /// scheduled if positive
     #[private]
     fn callback_after_swap_from(
         &mut self,
-        original_tx_hash: String,
-        new_address_for_near_receive: Option<ValidAccountId>,
-        min_amount_out: Option<U128>,
+        swap_from_event: SwapFromEvent,
+        transit_amount: U128,
+        amount_out: U128,
     ) {
-        assert_eq!(env::promise_results_count(), 1, "AfterSwap: Expected 1 promise result");
+        let transit_amount = u128::from(transit_amount);
+        let amount_out = u128::from(amount_out);
+        let payouts = [transit_amount, amount_out]
+            .iter()
+            .filter(|amount| **amount > 0)
+            .count();
+        assert_eq!(
+            env::promise_results_count(),
+            payouts as u64,
+            "AfterSwap: Expected promise result for every payout"
+        );
 
-        match env::promise_result(0) {
-            PromiseResult::Failed => {
-                env::log(b"SwapFromOTherBlockchain failed");
-            }
-            PromiseResult::Successful(_) => {
-                env::log(b"SwapFromOtherBlockchain");
+        let mut results = (0..payouts as u64)
+            .map(|index| matches!(env::promise_result(index), PromiseResult::Successful(_)));
+        let is_transit_paid = transit_amount == 0 || results.next().unwrap();
+        let is_out_paid = amount_out == 0 || results.next().unwrap();
 
-                // Transfer unwrapped NEAR to `new_address`
-                if let Some(min_amount_out) = min_amount_out {
-                    Promise::new(new_address_for_near_receive.unwrap().to_string())
-                        .transfer(min_amount_out.into());
-                }
+        self.complete_swap_from(
+            swap_from_event,
+            transit_amount,
+            is_transit_paid,
+            amount_out,
+            is_out_paid,
+        );
+    }
 
-                self.processed_tx.insert(&original_tx_hash);
-            }
-            PromiseResult::NotReady => {
-                unreachable!()
+    #[private]
+    fn callback_after_split_swap_to(
+        &mut self,
+        sender_id: AccountId,
+        token_in: AccountId,
+        amount_in: U128,
+        route_amounts: Vec<U128>,
+        route_amounts_out: Vec<U128>,
+        swap_to_params: SwapToParams,
+        integrator_fee: Option<IntegratorFee>,
+    ) {
+        let result = split::collect_split_swap_results(&route_amounts, &route_amounts_out);
+        let fee_amount = integrator::integrator_fee_amount(&integrator_fee);
+        let mut amount_returned = result.amount_returned;
+
+        let mut refund_token_ids = Vec::new();
+        let mut refund_amounts = Vec::new();
+
+        if result.failed_routes > 0 {
+            env::log(b"Split swap failed");
+
+            // Integrator fee is not taken from failed swap
+            amount_returned += fee_amount;
+
+            // Return outputs of executed routes to user as well
+            if result.amount_out > 0 {
+                refund_token_ids.push(self.transfer_token.clone());
+                refund_amounts.push(U128(result.amount_out));
             }
+        } else {
+            // RBC balance of sender is requested in parallel with swaps
+            let fee_discount = self.holder_fee_discount_of_result(route_amounts.len() as u64);
+
+            self.finalize_swap_to(
+                &sender_id,
+                &token_in,
+                u128::from(amount_in) - result.amount_returned,
+                result.amount_out,
+                swap_to_params,
+                &integrator_fee,
+                fee_discount,
+            );
+        }
+
+        if amount_returned > 0 {
+            refund_token_ids.push(token_in);
+            refund_amounts.push(U128(amount_returned));
+        }
+        if !refund_token_ids.is_empty() {
+            self.refund(sender_id, refund_token_ids, refund_amounts, false);
         }
     }
+
+    #[private]
+    fn callback_after_split_swap_from(
+        &mut self,
+        swap_from_event: SwapFromEvent,
+        new_address: ValidAccountId,
+        route_amounts: Vec<U128>,
+        route_amounts_out: Vec<U128>,
+    ) -> Promise {
+        let result = split::collect_split_swap_results(&route_amounts, &route_amounts_out);
+        let mut payouts = Vec::new();
+
+        if result.amount_out == 0 {
+            // User gets `transfer_token`
+            env::log(b"Split swap failed");
+        }
+        if result.amount_returned > 0 {
+            // `transfer_token` not used by failed routes goes to user as is
+            payouts.push(ext_fungible_token::ft_transfer(
+                new_address.to_string(),
+                U128(result.amount_returned),
+                None,
+                &self.transfer_token,
+                1,
+                self.gas_config.ft_transfer,
+            ));
+        }
+        if result.amount_out > 0 {
+            payouts.push(if swap_from_event.token_out == WRAP_NEAR {
+                ext_wrap::near_withdraw(
+                    U128(result.amount_out),
+                    &WRAP_NEAR.to_string(),
+                    1,
+                    self.gas_config.wrap_withdraw,
+                )
+            } else {
+                ext_fungible_token::ft_transfer(
+                    new_address.to_string(),
+                    U128(result.amount_out),
+                    None,
+                    &swap_from_event.token_out,
+                    1,
+                    self.gas_config.ft_transfer,
+                )
+            });
+        }
+
+        payouts
+            .into_iter()
+            .reduce(|payouts, payout| payouts.and(payout))
+            .unwrap()
+            .then(ext_self::callback_after_swap_from(
+                swap_from_event,
+                U128(result.amount_returned),
+                U128(result.amount_out),
+                &env::current_account_id(),
+                0,
+                self.gas_config.callback,
+            ))
+    }
 }
 
 // Internal methods implementations 
 impl Contract {
     fn assert_contract_running(&self) {
-        assert_eq!(
+        assert!(
             self.is_running,
-            true,
             "Contract is on pause",
         );
     }
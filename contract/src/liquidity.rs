@@ -0,0 +1,187 @@
+// List items in docs are aligned with descriptions of their items
+#![allow(clippy::doc_overindented_list_items)]
+
+use near_sdk::serde::Serialize;
+
+use crate::interfaces::TokenReceiverMessage;
+use super::*;
+
+/// Accounting of `transfer_token` held by this contract
+/// * `available` - liquidity for payouts of swaps from other blockchains
+/// * `reserved` - transit tokens of swaps in flight, escrows of swaps
+///                to other blockchains and payouts pending claim
+/// * `fees` - accrued fees of protocol, referrers and integrators
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Liquidity {
+    pub available: u128,
+    pub reserved: u128,
+    pub fees: u128,
+}
+
+/// Result of `get_liquidity` view
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LiquidityInfo {
+    pub available: U128,
+    pub reserved: U128,
+    pub fees: U128,
+}
+
+#[ext_contract(ext_self_liquidity)]
+pub trait LiquidityCallbacks {
+    fn callback_after_balance_of(&mut self);
+    fn callback_after_collect_token_fee(&mut self, amount: U128);
+    fn callback_after_pool_balancing(&mut self, amount: U128);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets available liquidity to balance of `transfer_token` which is 
+    /// neither reserved nor accrued as fees. Used for tokens transferred
+    /// to this contract without `ft_transfer_call`.
+    #[payable]
+    pub fn sync_liquidity(&mut self) -> Promise {
+        self.assert_owner_or_manager();
+
+        self.request_liquidity_sync()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[private]
+    pub fn callback_after_balance_of(&mut self) {
+        assert_eq!(env::promise_results_count(), 1, "AfterBalanceOf: Expected 1 promise result");
+
+        let balance = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .expect("Wrong balance format"),
+            _ => env::panic(b"Balance of transfer token is unavailable"),
+        };
+
+        self.liquidity.available = u128::from(balance)
+            .saturating_sub(self.liquidity.reserved + self.liquidity.fees);
+    }
+
+    /// Returns fee to accounting if transfer failed
+    #[private]
+    pub fn callback_after_collect_token_fee(&mut self, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "AfterCollect: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Collection of token fee failed");
+            self.acc_token_fee += u128::from(amount);
+            self.liquidity.fees += u128::from(amount);
+        }
+    }
+
+    /// Returns liquidity to accounting if transfer failed
+    #[private]
+    pub fn callback_after_pool_balancing(&mut self, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "AfterBalancing: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Pool balancing failed");
+            self.liquidity.available += u128::from(amount);
+        }
+    }
+}
+
+impl Contract {
+    /// Requests balance of `transfer_token` to set available liquidity
+    pub(crate) fn request_liquidity_sync(&self) -> Promise {
+        ext_fungible_token::ft_balance_of(
+            env::current_account_id(),
+            &self.transfer_token,
+            0,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_liquidity::callback_after_balance_of(
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+
+    /// Prepares accounting for change of `transfer_token` or its decimals.
+    /// Change is rejected while liquidity is owed in current token, 
+    /// available liquidity is synced with balance of new token.
+    pub(crate) fn reset_transfer_token_liquidity(&mut self) {
+        assert!(
+            self.liquidity.reserved == 0 && self.liquidity.fees == 0,
+            "Transfer token has reserved liquidity or accrued fees"
+        );
+        assert!(
+            self.lp_token.total_supply == 0 && self.queued_withdrawals == 0,
+            "Transfer token has liquidity of LPs"
+        );
+        self.assert_enough_gas(self.gas_config.ft_transfer + self.gas_config.callback);
+
+        self.liquidity.available = 0;
+        self.lp_liquidity = 0;
+    }
+
+    pub(crate) fn check_liquidity(&self, amount: u128) -> Result<(), &'static str> {
+        if amount > self.liquidity.available {
+            return Err("Not enough liquidity");
+        }
+
+        Ok(())
+    }
+
+    /// Reserves `gross_amount` of swap from other blockchain until payout. 
+    /// Fees are accrued only when payout is settled.
+    pub(crate) fn reserve_payout(&mut self, gross_amount: u128) {
+        if let Err(err) = self.check_liquidity(gross_amount) {
+            env::panic(err.as_bytes());
+        }
+
+        self.liquidity.available -= gross_amount;
+        self.liquidity.reserved += gross_amount;
+    }
+
+    /// Releases `gross_amount` reserved for payout. 
+    /// If nothing was paid amount is returned to available liquidity.
+    pub(crate) fn release_payout(&mut self, gross_amount: u128, is_paid: bool) {
+        self.liquidity.reserved -= gross_amount;
+        if !is_paid {
+            self.liquidity.available += gross_amount;
+        }
+    }
+
+    /// Records output of swap to other blockchain returned by DEX with
+    /// `swap_output_echo`. `min_amount_out` is reserved by swap callback,
+    /// the rest of `amount` is added to available liquidity.
+    /// Without echo the rest is picked up by sync of liquidity.
+    pub(crate) fn record_swap_output(
+        &mut self,
+        token_id: &AccountId,
+        sender_id: &AccountId,
+        amount: u128,
+        min_amount_out: u128,
+    ) {
+        assert_eq!(*token_id, self.transfer_token, "Swap output must be transfer token");
+        assert!(self.is_dex_router(sender_id), "Swap output must be sent by DEX");
+
+        self.liquidity.available += amount.saturating_sub(min_amount_out);
+    }
+
+    /// Message with which DEX returns output of swap to other blockchain
+    /// by `ft_transfer_call`, so actual output is recorded.
+    /// _None_ if echo is disabled and output is returned by plain transfer.
+    pub(crate) fn swap_output_echo(&self, min_amount_out: U128) -> Option<String> {
+        if !self.is_swap_output_echo_enabled {
+            return None;
+        }
+
+        Some(serde_json::to_string(&TokenReceiverMessage::SwapOutput { min_amount_out }).unwrap())
+    }
+
+    pub(crate) fn to_liquidity_info(&self) -> LiquidityInfo {
+        LiquidityInfo {
+            available: U128(self.liquidity.available),
+            reserved: U128(self.liquidity.reserved),
+            fees: U128(self.liquidity.fees),
+        }
+    }
+}
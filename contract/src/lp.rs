@@ -0,0 +1,373 @@
+use near_contract_standards::fungible_token::metadata::{
+    FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+};
+use near_sdk::{assert_one_yocto, PromiseOrValue};
+use near_sdk::serde::Serialize;
+
+use crate::interfaces::WithdrawalRequest;
+use super::*;
+
+// Arithmetic generated by the macro is not written in clippy style
+#[allow(clippy::assign_op_pattern)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+use u256::U256;
+
+near_contract_standards::impl_fungible_token_core!(Contract, lp_token);
+near_contract_standards::impl_fungible_token_storage!(Contract, lp_token);
+
+/// Result of `get_lp_pool` view
+/// * `lp_liquidity` - `transfer_token` owned by holders of LP shares
+/// * `total_shares` - total supply of LP shares
+/// * `queued_withdrawals` - `transfer_token` waiting in withdrawal queue
+/// * `queue_length` - number of requests waiting in withdrawal queue
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LpPoolInfo {
+    pub lp_liquidity: U128,
+    pub total_shares: U128,
+    pub queued_withdrawals: U128,
+    pub queue_length: u64,
+}
+
+#[ext_contract(ext_self_lp)]
+pub trait AfterLpWithdrawal {
+    fn callback_after_lp_withdrawal(&mut self, account_id: AccountId, amount: U128);
+}
+
+#[near_bindgen]
+impl FungibleTokenMetadataProvider for Contract {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Rubic cross-chain LP".to_string(),
+            symbol: "RUBIC-LP".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: self.transfer_token_decimals,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Burns `shares` of caller and withdraws their part of LP liquidity.
+    /// If available liquidity is low or other withdrawals are waiting,
+    /// request is put to the end of withdrawal queue.
+    /// Returns amount of `transfer_token` withdrawn or queued.
+    #[payable]
+    pub fn remove_liquidity(&mut self, shares: U128) -> U128 {
+        assert_one_yocto();
+        self.assert_contract_running();
+        let account_id = env::predecessor_account_id();
+        let shares = u128::from(shares);
+        assert!(shares > 0, "Shares must be positive");
+
+        let total_shares = self.lp_token.total_supply;
+        self.lp_token.internal_withdraw(&account_id, shares);
+        let amount = mul_div(shares, self.lp_liquidity, total_shares);
+        self.lp_liquidity -= amount;
+
+        if self.withdrawal_queue_head == self.withdrawal_queue.len() &&
+            self.check_liquidity(amount).is_ok()
+        {
+            self.assert_enough_gas(self.gas_config.ft_transfer + self.gas_config.callback);
+            self.transfer_lp_withdrawal(account_id, amount);
+        } else {
+            env::log(format!("Withdrawal of {} is queued", amount).as_bytes());
+            self.enqueue_withdrawal(account_id, amount);
+        }
+
+        U128(amount)
+    }
+
+    /// Pays queued withdrawals in order while available liquidity
+    /// and attached gas are enough. Returns number of paid requests.
+    pub fn process_withdrawal_queue(&mut self, limit: Option<u64>) -> u64 {
+        self.assert_contract_running();
+        let limit = limit.unwrap_or(u64::MAX);
+        let mut processed = 0;
+
+        while processed < limit && self.withdrawal_queue_head < self.withdrawal_queue.len() {
+            let request = self.withdrawal_queue.get(self.withdrawal_queue_head).unwrap();
+            let amount = u128::from(request.amount);
+            if self.check_liquidity(amount).is_err() ||
+                self.check_enough_gas(self.gas_config.ft_transfer + self.gas_config.callback)
+                    .is_err()
+            {
+                break;
+            }
+
+            self.withdrawal_queue_head += 1;
+            self.queued_withdrawals -= amount;
+            self.transfer_lp_withdrawal(request.account_id, amount);
+            processed += 1;
+        }
+
+        processed
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Puts withdrawal back to queue if transfer failed
+    #[private]
+    pub fn callback_after_lp_withdrawal(&mut self, account_id: AccountId, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "AfterLpWithdrawal: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Withdrawal of liquidity failed");
+            self.liquidity.available += u128::from(amount);
+            self.enqueue_withdrawal(account_id, u128::from(amount));
+        }
+    }
+}
+
+impl Contract {
+    /// Mints LP shares to `account_id` for `amount` of `transfer_token`
+    /// at current price of share. Returns number of minted shares.
+    pub(crate) fn provide_liquidity(&mut self, account_id: &AccountId, amount: u128) -> u128 {
+        assert!(amount > 0, "Amount must be positive");
+        assert!(
+            self.lp_token.accounts.get(account_id).is_some(),
+            "LP account is not registered"
+        );
+
+        let shares = if self.lp_token.total_supply == 0 || self.lp_liquidity == 0 {
+            amount
+        } else {
+            mul_div(amount, self.lp_token.total_supply, self.lp_liquidity)
+        };
+        assert!(shares > 0, "Amount is too small");
+
+        self.lp_token.internal_deposit(account_id, shares);
+        self.lp_liquidity += amount;
+        self.liquidity.available += amount;
+
+        shares
+    }
+
+    /// Moves `lp_fee_share` of protocol `fee` from accrued fees to LP
+    /// liquidity. Returns LP part of fee, zero if there are no LPs.
+    pub(crate) fn credit_lp_fee(&mut self, fee: u128) -> u128 {
+        if self.lp_token.total_supply == 0 {
+            return 0;
+        }
+
+        let lp_fee = fee * u128::from(self.lp_fee_share) / BASIS_POINTS_DENOMINATOR;
+        self.lp_liquidity += lp_fee;
+        self.liquidity.fees -= lp_fee;
+        self.liquidity.available += lp_fee;
+
+        lp_fee
+    }
+
+    /// Available liquidity which is neither owned by LPs
+    /// nor waiting in withdrawal queue
+    pub(crate) fn owner_liquidity(&self) -> u128 {
+        self.liquidity.available
+            .saturating_sub(self.lp_liquidity + self.queued_withdrawals)
+    }
+
+    fn enqueue_withdrawal(&mut self, account_id: AccountId, amount: u128) {
+        self.queued_withdrawals += amount;
+        self.withdrawal_queue.push(&WithdrawalRequest {
+            account_id,
+            amount: U128(amount),
+        });
+    }
+
+    fn transfer_lp_withdrawal(&mut self, account_id: AccountId, amount: u128) -> Promise {
+        self.liquidity.available -= amount;
+
+        ext_fungible_token::ft_transfer(
+            account_id.clone(),
+            U128(amount),
+            None,
+            &self.transfer_token,
+            1,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_lp::callback_after_lp_withdrawal(
+            account_id,
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+
+    pub(crate) fn to_lp_pool_info(&self) -> LpPoolInfo {
+        LpPoolInfo {
+            lp_liquidity: U128(self.lp_liquidity),
+            total_shares: U128(self.lp_token.total_supply),
+            queued_withdrawals: U128(self.queued_withdrawals),
+            queue_length: self.withdrawal_queue.len() - self.withdrawal_queue_head,
+        }
+    }
+}
+
+/// `a * b / c` without overflow of intermediate product
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    (U256::from(a) * U256::from(b) / U256::from(c)).as_u128()
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    use crate::gas::MAX_PREPAID_GAS;
+    use super::*;
+
+    const ALICE: &str = "alice.testnet";
+    const BOB: &str = "bob.testnet";
+
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.testnet".try_into().unwrap())
+            .attached_deposit(1)
+            .prepaid_gas(MAX_PREPAID_GAS)
+            .build());
+
+        let mut contract = Contract::new(
+            "owner.testnet".try_into().unwrap(),
+            "manager.testnet".try_into().unwrap(),
+            "relayer.testnet".try_into().unwrap(),
+            "usdt.testnet".try_into().unwrap(),
+            "ref-finance.testnet".try_into().unwrap(),
+            6,
+            0,
+            U128(0),
+            U128(u128::MAX),
+            U128(3000),
+            true,
+        );
+        contract.lp_fee_share = 5000;
+        contract.lp_token.internal_register_account(&ALICE.to_string());
+        contract.lp_token.internal_register_account(&BOB.to_string());
+
+        contract
+    }
+
+    fn set_predecessor(account_id: &str) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.try_into().unwrap())
+            .attached_deposit(1)
+            .prepaid_gas(MAX_PREPAID_GAS)
+            .build());
+    }
+
+    /// Accrues protocol `fee` of swap, LPs get `lp_fee_share` of it
+    fn accrue_fee(contract: &mut Contract, fee: u128) -> u128 {
+        contract.liquidity.fees += fee;
+        contract.credit_lp_fee(fee)
+    }
+
+    #[test]
+    fn mul_div_without_overflow() {
+        assert_eq!(mul_div(u128::MAX, 10, 20), u128::MAX / 2);
+        assert_eq!(mul_div(10, 3, 4), 7);
+        assert_eq!(mul_div(0, 5, 7), 0);
+    }
+
+    #[test]
+    fn first_deposit_mints_shares_one_to_one() {
+        let mut contract = setup();
+
+        assert_eq!(contract.provide_liquidity(&ALICE.to_string(), 1_000_000), 1_000_000);
+        assert_eq!(contract.lp_token.total_supply, 1_000_000);
+        assert_eq!(contract.lp_liquidity, 1_000_000);
+        assert_eq!(contract.liquidity.available, 1_000_000);
+    }
+
+    #[test]
+    fn no_lp_fee_without_shares() {
+        let mut contract = setup();
+
+        assert_eq!(accrue_fee(&mut contract, 1_000), 0);
+        assert_eq!(contract.liquidity.fees, 1_000);
+        assert_eq!(contract.lp_liquidity, 0);
+    }
+
+    #[test]
+    fn fee_accrual_raises_share_price() {
+        let mut contract = setup();
+        contract.provide_liquidity(&ALICE.to_string(), 1_000_000);
+
+        assert_eq!(accrue_fee(&mut contract, 400_000), 200_000);
+        assert_eq!(contract.lp_liquidity, 1_200_000);
+        assert_eq!(contract.liquidity.fees, 200_000);
+
+        // 1.2 of `transfer_token` per share
+        assert_eq!(contract.provide_liquidity(&BOB.to_string(), 600_000), 500_000);
+        assert_eq!(contract.lp_token.total_supply, 1_500_000);
+
+        set_predecessor(ALICE);
+        assert_eq!(contract.remove_liquidity(U128(1_000_000)), U128(1_200_000));
+        set_predecessor(BOB);
+        assert_eq!(contract.remove_liquidity(U128(500_000)), U128(600_000));
+        assert_eq!(contract.lp_liquidity, 0);
+        assert_eq!(contract.lp_token.total_supply, 0);
+    }
+
+    #[test]
+    fn burn_rounds_down() {
+        let mut contract = setup();
+        contract.provide_liquidity(&ALICE.to_string(), 3);
+        contract.provide_liquidity(&BOB.to_string(), 3);
+        accrue_fee(&mut contract, 2);
+
+        // 7 of `transfer_token` for 6 shares
+        set_predecessor(ALICE);
+        assert_eq!(contract.remove_liquidity(U128(1)), U128(1));
+        assert_eq!(contract.lp_liquidity, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount is too small")]
+    fn deposit_below_share_price() {
+        let mut contract = setup();
+        contract.provide_liquidity(&ALICE.to_string(), 1);
+        accrue_fee(&mut contract, 4);
+
+        // 3 of `transfer_token` per share
+        contract.provide_liquidity(&BOB.to_string(), 2);
+    }
+
+    #[test]
+    fn withdrawals_are_queued_without_liquidity() {
+        let mut contract = setup();
+        contract.provide_liquidity(&ALICE.to_string(), 1_000_000);
+        contract.provide_liquidity(&BOB.to_string(), 1_000_000);
+        contract.reserve_payout(1_500_000);
+
+        set_predecessor(ALICE);
+        assert_eq!(contract.remove_liquidity(U128(1_000_000)), U128(1_000_000));
+        let pool = contract.to_lp_pool_info();
+        assert_eq!(pool.queue_length, 1);
+        assert_eq!(pool.queued_withdrawals, U128(1_000_000));
+        assert_eq!(contract.liquidity.available, 500_000);
+
+        // Queue keeps order even if liquidity covers later withdrawal
+        set_predecessor(BOB);
+        assert_eq!(contract.remove_liquidity(U128(400_000)), U128(400_000));
+        assert_eq!(contract.to_lp_pool_info().queue_length, 2);
+        assert_eq!(contract.process_withdrawal_queue(None), 0);
+
+        contract.release_payout(1_500_000, false);
+        assert_eq!(contract.process_withdrawal_queue(Some(1)), 1);
+        assert_eq!(contract.to_lp_pool_info().queue_length, 1);
+        assert_eq!(contract.process_withdrawal_queue(None), 1);
+
+        let pool = contract.to_lp_pool_info();
+        assert_eq!(pool.queue_length, 0);
+        assert_eq!(pool.queued_withdrawals, U128(0));
+        assert_eq!(pool.lp_liquidity, U128(600_000));
+        assert_eq!(contract.liquidity.available, 600_000);
+    }
+}
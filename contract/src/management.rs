@@ -1,3 +1,5 @@
+use crate::liquidity::ext_self_liquidity;
+
 use super::*;
 
 #[near_bindgen]
@@ -20,10 +22,23 @@ impl Contract {
         self.relayer = relayer_id.as_ref().clone();
     }
 
+    /// Available liquidity is synced with balance of new token.
+    /// Rejected while liquidity is owed in current token
     #[payable]
     pub fn set_transfer_token(&mut self, transfer_token: ValidAccountId) {
         self.assert_owner();
+        self.reset_transfer_token_liquidity();
         self.transfer_token = transfer_token.as_ref().clone();
+        self.request_liquidity_sync();
+    }
+
+    /// Rejected while liquidity is owed in `transfer_token`
+    #[payable]
+    pub fn set_transfer_token_decimals(&mut self, transfer_token_decimals: u8) {
+        self.assert_owner();
+        self.reset_transfer_token_liquidity();
+        self.transfer_token_decimals = transfer_token_decimals;
+        self.request_liquidity_sync();
     }
 
     #[payable]
@@ -36,67 +51,271 @@ impl Contract {
     pub fn set_num_of_this_blockchain(&mut self, num_of_this_blockchain: u64) {
         self.assert_owner();
         self.num_of_this_blockchain = num_of_this_blockchain;
+        self.validate_config();
     }
 
     #[payable]
     pub fn set_min_token_amount(&mut self, min_token_amount: U128) {
         self.assert_owner_or_manager();
         self.min_token_amount = u128::from(min_token_amount);
+        self.validate_config();
     }
 
     #[payable]
     pub fn set_max_token_amount(&mut self, max_token_amount: U128) {
         self.assert_owner_or_manager();
         self.max_token_amount = u128::from(max_token_amount);
+        self.validate_config();
     }
 
     #[payable]
     pub fn set_is_running(&mut self, is_running: bool) {
         self.assert_owner_or_manager();
+        self.assert_no_upgrade_in_progress();
         self.is_running = is_running;
     }
 
+    /// Enables return of swap outputs to other blockchain with `client_echo`.
+    /// This contract must be whitelisted as echo sender by Ref Finance,
+    /// otherwise outputs are returned by plain transfer.
+    #[payable]
+    pub fn set_swap_output_echo(&mut self, is_enabled: bool) {
+        self.assert_owner_or_manager();
+        self.is_swap_output_echo_enabled = is_enabled;
+    }
+
+    /// Applies all set fields of `config_update` at once.
+    /// Whole update is rejected if resulting config is not valid.
+    #[payable]
+    pub fn update_config(&mut self, config_update: ConfigUpdate) {
+        self.assert_owner();
+        let is_transfer_token_changed = 
+            config_update.transfer_token.is_some() ||
+            config_update.transfer_token_decimals.is_some();
+        if is_transfer_token_changed {
+            self.reset_transfer_token_liquidity();
+        }
+
+        if let Some(manager) = config_update.manager {
+            self.manager = manager.as_ref().clone();
+        }
+        if let Some(relayer) = config_update.relayer {
+            self.relayer = relayer.as_ref().clone();
+        }
+        if let Some(transfer_token) = config_update.transfer_token {
+            self.transfer_token = transfer_token.as_ref().clone();
+        }
+        if let Some(transfer_token_decimals) = config_update.transfer_token_decimals {
+            self.transfer_token_decimals = transfer_token_decimals;
+        }
+        if let Some(blockchain_router) = config_update.blockchain_router {
+            self.blockchain_router = blockchain_router.as_ref().clone();
+        }
+        if let Some(num_of_this_blockchain) = config_update.num_of_this_blockchain {
+            self.num_of_this_blockchain = num_of_this_blockchain;
+        }
+        if let Some(min_token_amount) = config_update.min_token_amount {
+            self.min_token_amount = u128::from(min_token_amount);
+        }
+        if let Some(max_token_amount) = config_update.max_token_amount {
+            self.max_token_amount = u128::from(max_token_amount);
+        }
+        if let Some(fee_amount) = config_update.fee_amount_of_blockchain {
+            self.fee_amount_of_blockchain = u128::from(fee_amount);
+        }
+        if let Some(referral_fee_share) = config_update.referral_fee_share {
+            self.referral_fee_share = referral_fee_share;
+        }
+        if let Some(lp_fee_share) = config_update.lp_fee_share {
+            self.lp_fee_share = lp_fee_share;
+        }
+        if let Some(gas_config) = config_update.gas_config {
+            self.gas_config = gas_config;
+        }
+        if let Some(escrow_timeout) = config_update.escrow_timeout {
+            self.escrow_timeout = u64::from(escrow_timeout);
+        }
+        if let Some(is_enabled) = config_update.is_swap_output_echo_enabled {
+            self.is_swap_output_echo_enabled = is_enabled;
+        }
+        if let Some(is_running) = config_update.is_running {
+            self.assert_no_upgrade_in_progress();
+            self.is_running = is_running;
+        }
+
+        self.validate_config();
+        if is_transfer_token_changed {
+            self.request_liquidity_sync();
+        }
+    }
+
+    #[payable]
+    pub fn set_gas_config(&mut self, gas_config: GasConfig) {
+        self.assert_owner_or_manager();
+        self.gas_config = gas_config;
+        self.validate_config();
+    }
+
+    /// escrow_timeout is represented in nanoseconds,
+    /// it must not be less than 1 hour
+    #[payable]
+    pub fn set_escrow_timeout(&mut self, escrow_timeout: U64) {
+        self.assert_owner_or_manager();
+        self.escrow_timeout = u64::from(escrow_timeout);
+        self.validate_config();
+    }
+
     /// OTHERS BLOCKCHAIN MANAGEMENT
     #[payable]
-    pub fn add_other_blockchain(&mut self, blockchain_num: u64) {
+    pub fn add_other_blockchain(
+        &mut self, 
+        blockchain_num: u64,
+        blockchain_info: BlockchainInfo,
+    ) {
         self.assert_owner();
         assert!(
             blockchain_num != self.num_of_this_blockchain,
             "Cannot add this blockchain to array of other blockchains"
         );
         assert!(
-            self.existing_other_blockchain.insert(&blockchain_num),
+            self.other_blockchains
+                .insert(&blockchain_num, &blockchain_info)
+                .is_none(),
             "Blockchain already added"
         )
     }
 
+    #[payable]
+    pub fn update_other_blockchain(
+        &mut self, 
+        blockchain_num: u64,
+        blockchain_info: BlockchainInfo,
+    ) {
+        self.assert_owner();
+        assert!(
+            self.other_blockchains
+                .insert(&blockchain_num, &blockchain_info)
+                .is_some(),
+            "The blockchain was not added"
+        )
+    }
+
     #[payable]
     pub fn remove_other_blockchain(&mut self, blockchain_num: u64) {
         self.assert_owner();
         assert!(
-            self.existing_other_blockchain.remove(&blockchain_num),
+            self.other_blockchains.remove(&blockchain_num).is_some(),
             "The blockchain was not added"
         )
     }
 
+    /// DEX MANAGEMENT
+    #[payable]
+    pub fn add_dex(&mut self, dex_id: String, dex_info: DexInfo) {
+        self.assert_owner();
+        assert!(
+            env::is_valid_account_id(dex_info.router.as_bytes()),
+            "Wrong router account"
+        );
+        assert!(
+            self.dexes.insert(&dex_id, &dex_info).is_none(),
+            "Dex already added"
+        )
+    }
+
+    #[payable]
+    pub fn remove_dex(&mut self, dex_id: String) {
+        self.assert_owner();
+        assert!(
+            self.dexes.remove(&dex_id).is_some(),
+            "The dex was not added"
+        )
+    }
+
+    /// INTEGRATOR MANAGEMENT
+    #[payable]
+    pub fn add_integrator(
+        &mut self,
+        integrator_id: String,
+        integrator_info: IntegratorInfo,
+    ) {
+        self.assert_owner();
+        self.validate_integrator_info(&integrator_info);
+        assert!(
+            self.integrators
+                .insert(&integrator_id, &integrator_info)
+                .is_none(),
+            "Integrator already added"
+        )
+    }
+
+    #[payable]
+    pub fn update_integrator(
+        &mut self,
+        integrator_id: String,
+        integrator_info: IntegratorInfo,
+    ) {
+        self.assert_owner_or_manager();
+        self.validate_integrator_info(&integrator_info);
+        assert!(
+            self.integrators
+                .insert(&integrator_id, &integrator_info)
+                .is_some(),
+            "The integrator was not added"
+        )
+    }
+
+    /// Fee already accrued by integrator stays withdrawable
+    #[payable]
+    pub fn remove_integrator(&mut self, integrator_id: String) {
+        self.assert_owner();
+        assert!(
+            self.integrators.remove(&integrator_id).is_some(),
+            "The integrator was not added"
+        )
+    }
+
     /// FEE MANAGEMENT
     #[payable]
     pub fn collect_token_fee(&mut self) -> Promise {
         self.assert_owner();
+        let acc_token_fee = self.acc_token_fee;
+        self.acc_token_fee = 0;
+        self.liquidity.fees -= acc_token_fee;
 
         ext_fungible_token::ft_transfer(
             env::predecessor_account_id(),
-            U128(self.acc_token_fee),
+            U128(acc_token_fee),
             None,
             &self.transfer_token,
             1,
-            GAS_FOR_FT_TRANSFER,
+            self.gas_config.ft_transfer,
         )
+        .then(ext_self_liquidity::callback_after_collect_token_fee(
+            U128(acc_token_fee),
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
     }
 
+    /// Account which gets NEAR from `convert_fee_to_near`
+    #[payable]
+    pub fn set_fee_treasury(&mut self, fee_treasury: Option<ValidAccountId>) {
+        self.assert_owner();
+        self.fee_treasury = fee_treasury.map(|treasury| treasury.into());
+    }
+
+    /// Withdraws `amount` of available liquidity, reserved tokens, 
+    /// fees and liquidity of LPs can not be withdrawn
     #[payable]
     pub fn pool_balancing(&mut self, amount: U128) -> Promise {
         self.assert_owner();
+        assert!(
+            u128::from(amount) <= self.owner_liquidity(),
+            "Not enough liquidity"
+        );
+        self.liquidity.available -= u128::from(amount);
 
         ext_fungible_token::ft_transfer(
             env::predecessor_account_id(),
@@ -104,8 +323,14 @@ impl Contract {
             None,
             &self.transfer_token,
             1,
-            GAS_FOR_FT_TRANSFER,
+            self.gas_config.ft_transfer,
         )
+        .then(ext_self_liquidity::callback_after_pool_balancing(
+            amount,
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
     }
 
     #[payable]
@@ -115,9 +340,48 @@ impl Contract {
         rubic_address: String,
     ) {
         self.assert_owner_or_manager();
+        if let Some(blockchain) = self.other_blockchains.get(&blockchain_num) {
+            assert!(
+                blockchain.address_format.is_valid_address(&rubic_address),
+                "Wrong format of rubic address"
+            );
+        }
         self.rubic_addresses.insert(&blockchain_num, &rubic_address);
     }
 
+    /// REFERRAL MANAGEMENT
+    /// Account which gets referral fee of DEX swaps
+    #[payable]
+    pub fn set_dex_referral_id(&mut self, dex_referral_id: Option<ValidAccountId>) {
+        self.assert_owner();
+        self.dex_referral_id = dex_referral_id.map(|referral_id| referral_id.into());
+    }
+
+    /// referral_fee_share is represented in basis points of fee
+    #[payable]
+    pub fn set_referral_fee_share(&mut self, referral_fee_share: u16) {
+        self.assert_owner_or_manager();
+        self.referral_fee_share = referral_fee_share;
+        self.validate_config();
+    }
+
+    /// lp_fee_share is represented in basis points of fee
+    /// left after referral fee
+    #[payable]
+    pub fn set_lp_fee_share(&mut self, lp_fee_share: u16) {
+        self.assert_owner_or_manager();
+        self.lp_fee_share = lp_fee_share;
+        self.validate_config();
+    }
+
+    /// Fee rates of tiers are represented as hundredths of a bip, i.e. 1e-6
+    #[payable]
+    pub fn set_fee_schedule(&mut self, fee_schedule: FeeSchedule) {
+        self.assert_owner_or_manager();
+        self.fee_schedule = fee_schedule;
+        self.validate_config();
+    }
+
     /// fee_amount is represented as hundredths of a bip, i.e. 1e-6
     #[payable]
     pub fn set_fee_amount_of_blockchain(
@@ -126,6 +390,7 @@ impl Contract {
     ) {
         self.assert_owner_or_manager();
         self.fee_amount_of_blockchain = u128::from(fee_amount);
+        self.validate_config();
     }
 
     /// ACCESS CONTROL
@@ -0,0 +1,106 @@
+use super::*;
+
+#[ext_contract(ext_self_native)]
+pub trait AfterNearDeposit {
+    fn callback_after_near_deposit(
+        &mut self,
+        sender_id: AccountId,
+        amount_in: U128,
+        min_amount_out: U128,
+        swap_actions: Vec<Action>,
+        swap_to_params: SwapToParams,
+        dex: Option<String>,
+    ) -> Promise;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Represents SwapTokensToOtherBlockchain for attached NEAR.
+    /// NEAR is wrapped to `WRAP_NEAR` and swapped for `transfer_token`.
+    /// If wrap or swap fails NEAR is refunded to user.
+    /// * `swap_actions` - swap actions from `WRAP_NEAR` to `transfer_token`
+    /// * `swap_to_params` - params of swap in target blockchain
+    /// * `dex` - id of registered DEX. If _None_ Ref Finance is used
+    #[payable]
+    pub fn swap_near_to_other_blockchain(
+        &mut self,
+        swap_actions: Vec<Action>,
+        swap_to_params: SwapToParams,
+        dex: Option<String>,
+    ) -> Promise {
+        self.assert_contract_running();
+        self.validate_swap_actions(&swap_actions, &WRAP_NEAR.to_string());
+        self.validate_swap_to(&swap_to_params);
+
+        let amount_in = env::attached_deposit();
+        assert!(amount_in > 0, "Attached deposit must be positive");
+        let min_amount_out = swap_actions[swap_actions.len() - 1]
+            .min_amount_out()
+            .unwrap();
+        let callback_gas = 
+            self.gas_config.callback_near_deposit(&swap_actions) + 
+            self.holder_balance_gas();
+        self.assert_enough_gas(self.gas_config.wrap_deposit + callback_gas);
+
+        ext_wrap::near_deposit(
+            &WRAP_NEAR.to_string(),
+            amount_in,
+            self.gas_config.wrap_deposit,
+        )
+        .then(ext_self_native::callback_after_near_deposit(
+            env::predecessor_account_id(),
+            U128(amount_in),
+            min_amount_out,
+            swap_actions,
+            swap_to_params,
+            dex,
+            &env::current_account_id(),
+            0,
+            callback_gas,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Swaps wrapped NEAR for `transfer_token` or refunds NEAR
+    /// if wrap failed
+    #[private]
+    pub fn callback_after_near_deposit(
+        &mut self,
+        sender_id: AccountId,
+        amount_in: U128,
+        min_amount_out: U128,
+        swap_actions: Vec<Action>,
+        swap_to_params: SwapToParams,
+        dex: Option<String>,
+    ) -> Promise {
+        assert_eq!(env::promise_results_count(), 1, "AfterDeposit: Expected 1 promise result");
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                let integrator_fee = self.calculate_integrator_fee(
+                    &swap_to_params.integrator,
+                    u128::from(amount_in),
+                );
+
+                self.swap_tokens(
+                    sender_id,
+                    WRAP_NEAR.to_string(),
+                    amount_in,
+                    min_amount_out,
+                    swap_actions,
+                    swap_to_params,
+                    dex,
+                    integrator_fee,
+                    true,
+                )
+            }
+            _ => {
+                env::log(b"Wrap of NEAR failed");
+
+                Promise::new(sender_id).transfer(u128::from(amount_in))
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use super::*;
+
+/// Rounding of amounts which can not be represented exactly
+/// after decimals conversion
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Converts `amount` with `from_decimals` to representation with `to_decimals`
+pub fn convert_decimals(
+    amount: u128,
+    from_decimals: u8,
+    to_decimals: u8,
+    rounding: Rounding,
+) -> u128 {
+    checked_convert_decimals(amount, from_decimals, to_decimals, rounding)
+        .expect("Amount overflow on decimals conversion")
+}
+
+/// The same as `convert_decimals`, _None_ if converted amount overflows
+pub fn checked_convert_decimals(
+    amount: u128,
+    from_decimals: u8,
+    to_decimals: u8,
+    rounding: Rounding,
+) -> Option<u128> {
+    if from_decimals == to_decimals {
+        return Some(amount);
+    }
+
+    if to_decimals > from_decimals {
+        amount.checked_mul(pow10(to_decimals - from_decimals)?)
+    } else {
+        let divisor = match pow10(from_decimals - to_decimals) {
+            Some(divisor) => divisor,
+            // amount is always less than divisor
+            None if rounding == Rounding::Up && amount > 0 => return Some(1),
+            None => return Some(0),
+        };
+        let converted = amount / divisor;
+
+        if rounding == Rounding::Up && amount % divisor != 0 {
+            Some(converted + 1)
+        } else {
+            Some(converted)
+        }
+    }
+}
+
+fn pow10(exp: u8) -> Option<u128> {
+    10u128.checked_pow(u32::from(exp))
+}
+
+impl Contract {
+    /// Converts amount of transit token in other `blockchain`
+    /// to amount of `transfer_token`, _None_ if it overflows
+    pub(crate) fn normalize_amount_from(
+        &self,
+        blockchain: u64,
+        amount: u128,
+        rounding: Rounding,
+    ) -> Option<u128> {
+        checked_convert_decimals(
+            amount,
+            self.get_blockchain_info(blockchain).transit_token_decimals,
+            self.transfer_token_decimals,
+            rounding,
+        )
+    }
+
+    /// Converts amount of `transfer_token` to amount of transit token
+    /// in other `blockchain`
+    pub(crate) fn normalize_amount_to(
+        &self,
+        blockchain: u64,
+        amount: u128,
+        rounding: Rounding,
+    ) -> u128 {
+        convert_decimals(
+            amount,
+            self.transfer_token_decimals,
+            self.get_blockchain_info(blockchain).transit_token_decimals,
+            rounding,
+        )
+    }
+
+    pub(crate) fn get_blockchain_info(&self, blockchain: u64) -> BlockchainInfo {
+        self.other_blockchains
+            .get(&blockchain)
+            .expect("Wrong choose of blockchain")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_decimals() {
+        assert_eq!(convert_decimals(1_234_567, 6, 6, Rounding::Down), 1_234_567);
+        assert_eq!(convert_decimals(1_234_567, 6, 6, Rounding::Up), 1_234_567);
+    }
+
+    #[test]
+    fn more_decimals() {
+        // 1.5 USDT from 6 to 18 decimals
+        assert_eq!(
+            convert_decimals(1_500_000, 6, 18, Rounding::Down),
+            1_500_000_000_000_000_000
+        );
+        assert_eq!(
+            convert_decimals(1_500_000, 6, 18, Rounding::Up),
+            1_500_000_000_000_000_000
+        );
+    }
+
+    #[test]
+    fn less_decimals_exact() {
+        assert_eq!(
+            convert_decimals(1_500_000_000_000_000_000, 18, 6, Rounding::Down),
+            1_500_000
+        );
+        assert_eq!(
+            convert_decimals(1_500_000_000_000_000_000, 18, 6, Rounding::Up),
+            1_500_000
+        );
+    }
+
+    #[test]
+    fn less_decimals_with_remainder() {
+        assert_eq!(
+            convert_decimals(1_500_000_000_000_000_001, 18, 6, Rounding::Down),
+            1_500_000
+        );
+        assert_eq!(
+            convert_decimals(1_500_000_000_000_000_001, 18, 6, Rounding::Up),
+            1_500_001
+        );
+        assert_eq!(convert_decimals(999_999_999_999, 18, 6, Rounding::Down), 0);
+        assert_eq!(convert_decimals(999_999_999_999, 18, 6, Rounding::Up), 1);
+    }
+
+    #[test]
+    fn checked_overflow() {
+        assert_eq!(checked_convert_decimals(u128::MAX, 0, 18, Rounding::Down), None);
+        assert_eq!(checked_convert_decimals(1, 0, 40, Rounding::Down), None);
+        assert_eq!(checked_convert_decimals(u128::MAX, 40, 0, Rounding::Down), Some(0));
+        assert_eq!(checked_convert_decimals(u128::MAX, 40, 0, Rounding::Up), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount overflow on decimals conversion")]
+    fn overflow() {
+        convert_decimals(u128::MAX, 0, 18, Rounding::Down);
+    }
+}
@@ -0,0 +1,171 @@
+use super::*;
+
+#[ext_contract(ext_self_payout)]
+pub trait AfterPayout {
+    fn callback_after_claim_payout(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    );
+    fn callback_after_refund(
+        &mut self,
+        account_id: AccountId,
+        token_ids: Vec<AccountId>,
+        amounts: Vec<U128>,
+        refund_in_near: bool,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers payout of `token_id` which could not be transferred
+    /// to caller by swap from other blockchain or by refund
+    #[payable]
+    pub fn claim_payout(&mut self, token_id: ValidAccountId) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let token_id: AccountId = token_id.into();
+        let amount = self.pending_payouts
+            .remove(&(account_id.clone(), token_id.clone()))
+            .unwrap_or(0);
+        assert!(amount > 0, "No payout to claim");
+        if token_id == self.transfer_token {
+            self.liquidity.reserved -= amount;
+        }
+
+        ext_fungible_token::ft_transfer(
+            account_id.clone(),
+            U128(amount),
+            None,
+            &token_id,
+            1,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_payout::callback_after_claim_payout(
+            account_id,
+            token_id,
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns payout to balance if transfer failed
+    #[private]
+    pub fn callback_after_claim_payout(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "AfterClaim: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Claim of payout failed");
+            self.credit_pending_payout(&account_id, &token_id, u128::from(amount));
+        }
+    }
+
+    /// Sends unwrapped NEAR if refund is made in NEAR.
+    /// Refunds which failed can be claimed by `claim_payout`.
+    #[private]
+    pub fn callback_after_refund(
+        &mut self,
+        account_id: AccountId,
+        token_ids: Vec<AccountId>,
+        amounts: Vec<U128>,
+        refund_in_near: bool,
+    ) {
+        assert_eq!(
+            env::promise_results_count(),
+            token_ids.len() as u64,
+            "AfterRefund: Expected promise result for every refund"
+        );
+
+        for (index, token_id) in token_ids.iter().enumerate() {
+            let amount = u128::from(amounts[index]);
+            match env::promise_result(index as u64) {
+                PromiseResult::Successful(_) => {
+                    if refund_in_near && token_id == WRAP_NEAR {
+                        Promise::new(account_id.clone()).transfer(amount);
+                    }
+                }
+                _ => {
+                    env::log(b"Refund failed, it can be claimed");
+                    self.credit_pending_payout(&account_id, token_id, amount);
+                }
+            }
+        }
+    }
+}
+
+impl Contract {
+    /// Refunds `amounts` of `token_ids` to `account_id` in parallel. 
+    /// wrap.near is unwrapped and sent as NEAR if `refund_in_near`.
+    pub(crate) fn refund(
+        &self,
+        account_id: AccountId,
+        token_ids: Vec<AccountId>,
+        amounts: Vec<U128>,
+        refund_in_near: bool,
+    ) -> Promise {
+        token_ids
+            .iter()
+            .zip(amounts.iter())
+            .map(|(token_id, amount)| {
+                if refund_in_near && token_id == WRAP_NEAR {
+                    ext_wrap::near_withdraw(
+                        *amount,
+                        &WRAP_NEAR.to_string(),
+                        1,
+                        self.gas_config.wrap_withdraw,
+                    )
+                } else {
+                    ext_fungible_token::ft_transfer(
+                        account_id.clone(),
+                        *amount,
+                        None,
+                        token_id,
+                        1,
+                        self.gas_config.ft_transfer,
+                    )
+                }
+            })
+            .reduce(|refunds, refund| refunds.and(refund))
+            .unwrap()
+            .then(ext_self_payout::callback_after_refund(
+                account_id,
+                token_ids,
+                amounts,
+                refund_in_near,
+                &env::current_account_id(),
+                0,
+                self.gas_config.callback,
+            ))
+    }
+
+    /// Credits `amount` of `token_id` to be claimed by `account_id`.
+    /// Pending `transfer_token` is reserved.
+    pub(crate) fn credit_pending_payout(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &AccountId,
+        amount: u128,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        let key = (account_id.clone(), token_id.clone());
+        let balance = self.pending_payouts.get(&key).unwrap_or(0);
+        self.pending_payouts.insert(&key, &(balance + amount));
+
+        if *token_id == self.transfer_token {
+            self.liquidity.reserved += amount;
+        }
+    }
+}
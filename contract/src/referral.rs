@@ -0,0 +1,90 @@
+use super::*;
+
+#[ext_contract(ext_self_referral)]
+pub trait AfterClaimReferralFee {
+    fn callback_after_claim_referral_fee(
+        &mut self,
+        referrer_id: AccountId,
+        amount: U128,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers referral fee accrued by caller in `transfer_token`
+    #[payable]
+    pub fn claim_referral_fee(&mut self) -> Promise {
+        let referrer_id = env::predecessor_account_id();
+        let amount = self.referral_balances
+            .remove(&referrer_id)
+            .unwrap_or(0);
+        assert!(amount > 0, "No referral fee to claim");
+        self.liquidity.fees -= amount;
+
+        ext_fungible_token::ft_transfer(
+            referrer_id.clone(),
+            U128(amount),
+            None,
+            &self.transfer_token,
+            1,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_referral::callback_after_claim_referral_fee(
+            referrer_id,
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns referral fee to balance if transfer failed
+    #[private]
+    pub fn callback_after_claim_referral_fee(
+        &mut self,
+        referrer_id: AccountId,
+        amount: U128,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "AfterClaim: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Claim of referral fee failed");
+            self.credit_referral_balance(&referrer_id, u128::from(amount));
+            self.liquidity.fees += u128::from(amount);
+        }
+    }
+}
+
+impl Contract {
+    /// Credits `referral_fee_share` of `fee` to `referrer` if it is set.
+    /// Returns credited amount.
+    pub(crate) fn credit_referral_fee(
+        &mut self,
+        referrer: &Option<AccountId>,
+        fee: u128,
+    ) -> u128 {
+        match referrer {
+            Some(referrer) => {
+                let referral_fee = 
+                    fee * u128::from(self.referral_fee_share) / 
+                    BASIS_POINTS_DENOMINATOR;
+                if referral_fee > 0 {
+                    self.credit_referral_balance(referrer, referral_fee);
+                }
+
+                referral_fee
+            }
+            None => 0,
+        }
+    }
+
+    fn credit_referral_balance(&mut self, referrer_id: &AccountId, amount: u128) {
+        let balance = self.referral_balances
+            .get(referrer_id)
+            .unwrap_or(0);
+        self.referral_balances.insert(referrer_id, &(balance + amount));
+    }
+}
@@ -0,0 +1,112 @@
+use super::*;
+
+/// 1 NEAR in yoctoNEAR
+pub const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+
+#[ext_contract(ext_self_relayer)]
+pub trait AfterWithdrawRelayerCompensation {
+    fn callback_after_withdraw_relayer_compensation(&mut self, amount: U128);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets compensation of relayer gas for swaps from `blockchain_num`.
+    /// Compensation is not taken if _None_. Compensation of gas requires
+    /// NEAR price to be set.
+    #[payable]
+    pub fn set_relayer_compensation(
+        &mut self,
+        blockchain_num: u64,
+        compensation: Option<RelayerCompensation>,
+    ) {
+        self.assert_owner_or_manager();
+        let is_gas = matches!(compensation, Some(RelayerCompensation::Gas { .. }));
+        if is_gas {
+            assert!(self.near_price > 0, "NEAR price is not set");
+        }
+
+        let previous = match compensation {
+            Some(compensation) => {
+                self.relayer_compensations.insert(&blockchain_num, &compensation)
+            }
+            None => {
+                self.relayer_compensations.remove(&blockchain_num)
+            }
+        };
+        if let Some(RelayerCompensation::Gas { .. }) = previous {
+            self.gas_compensations_count -= 1;
+        }
+        if is_gas {
+            self.gas_compensations_count += 1;
+        }
+    }
+
+    /// near_price is represented as amount of `transfer_token` per 1 NEAR.
+    /// It can not be zero while compensation of gas is set.
+    #[payable]
+    pub fn set_near_price(&mut self, near_price: U128) {
+        self.assert_owner_or_manager();
+        assert!(
+            u128::from(near_price) > 0 || self.gas_compensations_count == 0,
+            "NEAR price is used by compensation of gas"
+        );
+        self.near_price = u128::from(near_price);
+    }
+
+    /// Transfers accrued relayer compensation in `transfer_token` to relayer
+    #[payable]
+    pub fn withdraw_relayer_compensation(&mut self) -> Promise {
+        self.assert_relayer();
+        let amount = self.relayer_compensation_balance;
+        assert!(amount > 0, "No relayer compensation to withdraw");
+        self.relayer_compensation_balance = 0;
+        self.liquidity.fees -= amount;
+
+        ext_fungible_token::ft_transfer(
+            env::predecessor_account_id(),
+            U128(amount),
+            None,
+            &self.transfer_token,
+            1,
+            self.gas_config.ft_transfer,
+        )
+        .then(ext_self_relayer::callback_after_withdraw_relayer_compensation(
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            self.gas_config.callback,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns compensation to balance if transfer failed
+    #[private]
+    pub fn callback_after_withdraw_relayer_compensation(&mut self, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "AfterWithdraw: Expected 1 promise result");
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            env::log(b"Withdrawal of relayer compensation failed");
+            self.accrue_relayer_compensation(u128::from(amount));
+        }
+    }
+}
+
+impl Contract {
+    /// Compensation of relayer gas in `transfer_token` 
+    /// for swap from other `blockchain`
+    pub(crate) fn relayer_compensation_amount(&self, blockchain: u64) -> u128 {
+        match self.relayer_compensations.get(&blockchain) {
+            Some(RelayerCompensation::Flat { amount }) => u128::from(amount),
+            Some(RelayerCompensation::Gas { near_amount }) => 
+                u128::from(near_amount) * self.near_price / ONE_NEAR,
+            None => 0,
+        }
+    }
+
+    pub(crate) fn accrue_relayer_compensation(&mut self, amount: u128) {
+        self.relayer_compensation_balance += amount;
+        self.liquidity.fees += amount;
+    }
+}
@@ -0,0 +1,309 @@
+use crate::dex::DexAdapter;
+use crate::interfaces::SwapRoute;
+
+use super::*;
+
+pub const MAX_SWAP_ROUTES: usize = 3;
+/// Shares of swap routes are represented in basis points
+pub const SHARE_DENOMINATOR: u128 = 10_000;
+
+/// Result of split swap routes executed by `instant_swap`
+/// * `amount_out` - sum of min amounts out of executed routes
+/// * `amount_returned` - amount in returned by DEX
+/// * `failed_routes` - number of routes which were not executed
+pub(crate) struct SplitSwapResult {
+    pub amount_out: u128,
+    pub amount_returned: u128,
+    pub failed_routes: usize,
+}
+
+/// Splits `amount` by shares of `routes`. Last route gets the remainder.
+pub(crate) fn split_amount(
+    amount: u128,
+    routes: &[SwapRoute],
+) -> Result<Vec<U128>, &'static str> {
+    let mut route_amounts: Vec<U128> = routes[..routes.len() - 1]
+        .iter()
+        .map(|route| U128(amount * u128::from(route.share) / SHARE_DENOMINATOR))
+        .collect();
+    let distributed: u128 = route_amounts.iter().map(|amount| amount.0).sum();
+    route_amounts.push(U128(amount - distributed));
+
+    if !route_amounts.iter().all(|amount| amount.0 > 0) {
+        return Err("Amount of every swap route must be positive");
+    }
+
+    Ok(route_amounts)
+}
+
+/// Reads results of `ft_transfer_call`s scheduled by `schedule_split_swaps`.
+/// Each result is the amount used by DEX. Results of routes go first,
+/// result of request joined to swaps may follow.
+pub(crate) fn collect_split_swap_results(
+    route_amounts: &[U128],
+    route_amounts_out: &[U128],
+) -> SplitSwapResult {
+    assert!(
+        env::promise_results_count() >= route_amounts.len() as u64,
+        "AfterSwap: Expected promise result for every swap route"
+    );
+
+    let mut result = SplitSwapResult {
+        amount_out: 0,
+        amount_returned: 0,
+        failed_routes: 0,
+    };
+    for (index, route_amount) in route_amounts.iter().enumerate() {
+        let used_amount = match env::promise_result(index as u64) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(u128::from)
+                .unwrap_or(0),
+            _ => 0,
+        };
+
+        if used_amount > 0 {
+            result.amount_out += route_amounts_out[index].0;
+        } else {
+            result.failed_routes += 1;
+        }
+        result.amount_returned += route_amount.0 - used_amount;
+    }
+
+    result
+}
+
+impl Contract {
+    /// Checks routes of split swap from `token_in` to `token_out`.
+    /// Sum of min amounts out of all routes must cover `min_amount_out`.
+    /// Returns min amount out of every route.
+    pub(crate) fn check_swap_routes(
+        &self,
+        routes: &[SwapRoute],
+        token_in: &AccountId,
+        token_out: &AccountId,
+        min_amount_out: U128,
+    ) -> Result<Vec<U128>, &'static str> {
+        if routes.is_empty() || routes.len() > MAX_SWAP_ROUTES {
+            return Err("Wrong number of swap routes");
+        }
+        if !routes.iter().all(|route| route.share > 0) {
+            return Err("Share of every swap route must be positive");
+        }
+        if routes.iter().map(|route| u128::from(route.share)).sum::<u128>() != 
+            SHARE_DENOMINATOR 
+        {
+            return Err("Shares of swap routes must sum to 10000");
+        }
+
+        let route_amounts_out = routes
+            .iter()
+            .map(|route| {
+                self.check_swap_route(&route.actions, token_in, token_out)?;
+
+                route.actions
+                    .last()
+                    .unwrap()
+                    .min_amount_out()
+                    .ok_or("Last action in swap route must have amount out")
+            })
+            .collect::<Result<Vec<U128>, &'static str>>()?;
+
+        if route_amounts_out.iter().map(|amount| amount.0).sum::<u128>() <
+            u128::from(min_amount_out)
+        {
+            return Err("Min amounts out of swap routes do not cover min amount out");
+        }
+
+        Ok(route_amounts_out)
+    }
+
+    pub(crate) fn validate_swap_routes(
+        &self,
+        routes: &[SwapRoute],
+        token_in: &AccountId,
+        token_out: &AccountId,
+        min_amount_out: U128,
+    ) -> Vec<U128> {
+        self.check_swap_routes(routes, token_in, token_out, min_amount_out)
+            .unwrap_or_else(|err| env::panic(err.as_bytes()))
+    }
+
+    /// Swaps `route_amounts` of `token_in` by routes in parallel.
+    /// Results of returned joint promise are read by `collect_split_swap_results`.
+    /// If `route_amounts_out` are set and echo is enabled, 
+    /// outputs are recorded by `record_swap_output`.
+    pub(crate) fn schedule_split_swaps(
+        &self,
+        dex: &dyn DexAdapter,
+        token_in: &AccountId,
+        route_amounts: &[U128],
+        route_amounts_out: Option<&[U128]>,
+        routes: Vec<SwapRoute>,
+    ) -> Promise {
+        routes
+            .into_iter()
+            .enumerate()
+            .map(|(index, route)| {
+                let swap_gas = self.gas_config.swap(route.actions.len());
+                let mut actions = route.actions;
+                actions[0].set_amount_in(route_amounts[index]);
+
+                dex.instant_swap(
+                    token_in,
+                    route_amounts[index],
+                    actions,
+                    route_amounts_out
+                        .and_then(|amounts_out| self.swap_output_echo(amounts_out[index])),
+                    swap_gas,
+                )
+            })
+            .reduce(|swaps, swap| swaps.and(swap))
+            .unwrap()
+    }
+}
+
+impl Contract {
+    /// Swaps `token_in` for `transfer_token` by several routes
+    /// and emits swapToOther event in `callback_after_split_swap_to`
+    pub(crate) fn split_swap_tokens(
+        &self,
+        sender_id: AccountId,
+        token_in: AccountId,
+        amount_in: U128,
+        min_amount_out: U128,
+        routes: Vec<SwapRoute>,
+        swap_to_params: SwapToParams,
+        dex_id: Option<String>,
+        integrator_fee: Option<IntegratorFee>,
+    ) -> Promise {
+        let route_amounts_out = self.validate_swap_routes(
+            &routes,
+            &token_in,
+            &self.transfer_token,
+            min_amount_out,
+        );
+        // Integrator fee stays on this contract, rest is swapped
+        let route_amounts = split_amount(
+            u128::from(amount_in) - integrator::integrator_fee_amount(&integrator_fee),
+            &routes,
+        )
+        .unwrap_or_else(|err| env::panic(err.as_bytes()));
+        let dex = self.get_dex_adapter(&dex_id);
+        self.assert_enough_gas(
+            self.gas_config.split_swap(&routes) + 
+            self.holder_balance_gas() +
+            self.gas_config.callback_split_swap_to
+        );
+
+        let swaps = self.schedule_split_swaps(
+            dex.as_ref(),
+            &token_in,
+            &route_amounts,
+            Some(&route_amounts_out),
+            routes,
+        );
+        // RBC balance of sender for fee discount
+        let swaps = match self.request_holder_balance(&sender_id) {
+            Some(request) => swaps.and(request),
+            None => swaps,
+        };
+
+        swaps
+            .then(ext_self::callback_after_split_swap_to(
+                sender_id,
+                token_in,
+                amount_in,
+                route_amounts,
+                route_amounts_out,
+                swap_to_params,
+                integrator_fee,
+                &env::current_account_id(),
+                0,
+                self.gas_config.callback_split_swap_to,
+            ))
+    }
+
+    /// Swaps `amount_in_without_fee` of `transfer_token` for `token_out`
+    /// by several routes and transfers outputs to user in 
+    /// `callback_after_split_swap_from`
+    pub(crate) fn split_swap_tokens_to_user(
+        &self,
+        params: SwapFromParams,
+        amount_in_without_fee: u128,
+        routes: Vec<SwapRoute>,
+        swap_from_event: SwapFromEvent,
+    ) -> Promise {
+        let route_amounts_out = self.validate_swap_routes(
+            &routes,
+            &self.transfer_token,
+            params.token_out.as_ref(),
+            params.amount_out_min,
+        );
+        let route_amounts = split_amount(amount_in_without_fee, &routes)
+            .unwrap_or_else(|err| env::panic(err.as_bytes()));
+        let dex = self.get_dex_adapter(&params.dex);
+
+        self.schedule_split_swaps(
+            dex.as_ref(),
+            &self.transfer_token,
+            &route_amounts,
+            None,
+            routes,
+        )
+            .then(ext_self::callback_after_split_swap_from(
+                swap_from_event,
+                params.new_address,
+                route_amounts,
+                route_amounts_out,
+                &env::current_account_id(),
+                0,
+                self.gas_config.callback_split_swap_from,
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes(shares: &[u16]) -> Vec<SwapRoute> {
+        shares
+            .iter()
+            .map(|share| SwapRoute { share: *share, actions: Vec::new() })
+            .collect()
+    }
+
+    fn amounts(route_amounts: Vec<U128>) -> Vec<u128> {
+        route_amounts.into_iter().map(u128::from).collect()
+    }
+
+    #[test]
+    fn split_by_shares() {
+        let route_amounts = split_amount(1_000_000, &routes(&[5_000, 3_000, 2_000])).unwrap();
+
+        assert_eq!(amounts(route_amounts), vec![500_000, 300_000, 200_000]);
+    }
+
+    #[test]
+    fn remainder_goes_to_last_route() {
+        let route_amounts = split_amount(1_001, &routes(&[3_333, 3_333, 3_334])).unwrap();
+
+        // 1001 * 3333 / 10000 = 333.6333 is rounded down
+        assert_eq!(amounts(route_amounts), vec![333, 333, 335]);
+    }
+
+    #[test]
+    fn single_route() {
+        let route_amounts = split_amount(12_345, &routes(&[10_000])).unwrap();
+
+        assert_eq!(amounts(route_amounts), vec![12_345]);
+    }
+
+    #[test]
+    fn zero_amount_of_route() {
+        assert_eq!(
+            split_amount(3, &routes(&[1_000, 9_000])).err(),
+            Some("Amount of every swap route must be positive")
+        );
+    }
+}
@@ -13,55 +13,147 @@ impl FungibleTokenReceiver for Contract {
     /// Swap `token_in` for `transfer_token` via ref-finance 
     /// or just emit swapToOther event if `token_in` is `transfer_token`.
     /// * `msg` format is `TokenReceiverMessage`.
-    #[allow(unreachable_code)]
     fn ft_on_transfer(
         &mut self, 
         sender_id: ValidAccountId, 
         amount: U128, 
         msg: String
     ) -> PromiseOrValue<U128> {
-        self.assert_contract_running();
         let token_in = env::predecessor_account_id();
+        let message = serde_json::from_str::<TokenReceiverMessage>(&msg)
+            .expect("Receiver - Wrong TokenReceiverMessage format");
 
-        serde_json::from_str::<TokenReceiverMessage>(&msg)
-            .and_then(|message| {
-                match message {
-                    TokenReceiverMessage::SwapTokensToOther {
-                        swap_actions,
-                        swap_to_params,
-                    } => {
-                        self.validate_swap_actions(&swap_actions);
-                        self.validate_swap_to(&swap_to_params);
-                        self.validate_token_in_is_not_transfer(&token_in);
+        // Output of swap in flight is accepted on pause
+        if let TokenReceiverMessage::SwapOutput { min_amount_out } = message {
+            self.record_swap_output(
+                &token_in,
+                sender_id.as_ref(),
+                u128::from(amount),
+                u128::from(min_amount_out),
+            );
+
+            return PromiseOrValue::Value(U128(0));
+        }
+        self.assert_contract_running();
+
+        match message {
+            TokenReceiverMessage::SwapTokensToOther {
+                swap_actions,
+                swap_to_params,
+                dex,
+            } => {
+                self.validate_swap_actions(&swap_actions, &token_in);
+                self.validate_swap_to(&swap_to_params);
+                self.validate_token_in_is_not_transfer(&token_in);
 
-                        let swaps_len = swap_actions.len();
-                        let min_amount_out = 
-                            swap_actions[swaps_len-1].min_amount_out;
+                let swaps_len = swap_actions.len();
+                let min_amount_out = 
+                    swap_actions[swaps_len-1].min_amount_out().unwrap();
+                let integrator_fee = self.calculate_integrator_fee(
+                    &swap_to_params.integrator,
+                    u128::from(amount),
+                );
 
-                        self.swap_tokens(
+                self.swap_tokens(
+                    sender_id.to_string(),
+                    token_in, 
+                    amount,
+                    min_amount_out,
+                    swap_actions,
+                    swap_to_params,
+                    dex,
+                    integrator_fee,
+                    false,
+                );
+            },
+            TokenReceiverMessage::SplitSwapTokensToOther {
+                routes,
+                min_amount_out,
+                swap_to_params,
+                dex,
+            } => {
+                self.validate_amount_in(&min_amount_out);
+                self.validate_swap_to(&swap_to_params);
+                self.validate_token_in_is_not_transfer(&token_in);
+
+                let integrator_fee = self.calculate_integrator_fee(
+                    &swap_to_params.integrator,
+                    u128::from(amount),
+                );
+
+                self.split_swap_tokens(
+                    sender_id.to_string(),
+                    token_in,
+                    amount,
+                    min_amount_out,
+                    routes,
+                    swap_to_params,
+                    dex,
+                    integrator_fee,
+                );
+            },
+            TokenReceiverMessage::SwapTransferTokensToOther {
+                swap_to_params,
+            } => {
+                self.validate_swap_to(&swap_to_params);
+                self.validate_amount_in(&amount);
+                self.validate_token_in_is_transfer(&token_in);
+
+                let integrator_fee = self.calculate_integrator_fee(
+                    &swap_to_params.integrator,
+                    u128::from(amount),
+                );
+                let transit_amount = self.calculate_transfer_to(
+                    swap_to_params.blockchain,
+                    u128::from(amount),
+                    sender_id.as_ref(),
+                    &integrator_fee,
+                    0,
+                ).net_amount;
+
+                match self.request_holder_balance(sender_id.as_ref()) {
+                    Some(request) => {
+                        self.assert_enough_gas(
+                            self.holder_balance_gas() + self.gas_config.callback
+                        );
+
+                        request.then(discount::ext_self_discount::callback_after_sender_balance(
                             sender_id.to_string(),
-                            token_in, 
+                            token_in,
                             amount,
-                            min_amount_out,
-                            swap_actions,
-                        );
-                    },
-                    TokenReceiverMessage::SwapTransferTokensToOther {
+                            U128(transit_amount),
+                            swap_to_params,
+                            integrator_fee,
+                            &env::current_account_id(),
+                            0,
+                            self.gas_config.callback,
+                        ));
+                    }
+                    None => self.finalize_swap_to(
+                        sender_id.as_ref(),
+                        &token_in,
+                        u128::from(amount),
+                        transit_amount,
                         swap_to_params,
-                    } => {
-                        self.validate_swap_to(&swap_to_params);
-                        self.validate_amount_in(&amount);
-                        self.validate_token_in_is_transfer(&token_in);
-
-                        env::log(b"SwapToOtherBlockchain");
-                    },
-                    _ => unreachable!()
+                        &integrator_fee,
+                        0,
+                    ),
                 }
+            },
+            TokenReceiverMessage::ProvideLiquidity => {
+                self.validate_token_in_is_transfer(&token_in);
+
+                let shares = self.provide_liquidity(
+                    sender_id.as_ref(),
+                    u128::from(amount),
+                );
+                env::log(
+                    format!("Minted {} LP shares to {}", shares, sender_id).as_bytes()
+                );
+            },
+            TokenReceiverMessage::SwapOutput { .. } => unreachable!(),
+        }
 
-               Ok(())
-            })
-            .expect("Receiver - Wrong TokenReceiverMessage format");
-           
         PromiseOrValue::Value(U128(0))
     }
 }
@@ -73,32 +165,51 @@ impl Contract {
         token_in: AccountId,
         amount_in: U128,
         min_amount_out: U128,
-        actions: Vec<SwapAction>,
+        mut actions: Vec<Action>,
+        swap_to_params: SwapToParams,
+        dex_id: Option<String>,
+        integrator_fee: Option<IntegratorFee>,
+        refund_in_near: bool,
     ) -> Promise {
-        ext_fungible_token::ft_transfer_call(
-            self.get_blockchain_router(),
-            amount_in,
-            None,
-            "".to_string(),
+        let dex = self.get_dex_adapter(&dex_id);
+        let swap_gas = self.gas_config.swap(actions.len());
+        self.assert_enough_gas(
+            swap_gas + 
+            self.holder_balance_gas() + 
+            self.gas_config.callback_swap_to
+        );
+
+        // Integrator fee stays on this contract, rest is swapped
+        let fee_amount = integrator::integrator_fee_amount(&integrator_fee);
+        let amount_to_swap = U128(u128::from(amount_in) - fee_amount);
+        actions[0].set_amount_in(amount_to_swap);
+
+        // Output of swap is sent back to this contract by DEX
+        // and recorded by `record_swap_output` if echo is enabled
+        let swap = dex.instant_swap(
             &token_in,
-            1,
-            GAS_FOT_FT_TRANSFER_CALL,
-        )
-        .then(ext_ref::swap(
+            amount_to_swap,
             actions,
-            None,
-            &self.blockchain_router,
-            0,
-            GAS_FOR_SWAP,
-        ))
-        .then(ext_self::callback_after_swap_to(
+            self.swap_output_echo(min_amount_out),
+            swap_gas,
+        );
+        // RBC balance of sender for fee discount
+        let swap = match self.request_holder_balance(&sender_id) {
+            Some(request) => swap.and(request),
+            None => swap,
+        };
+
+        swap.then(ext_self::callback_after_swap_to(
             sender_id.to_string(),
             token_in,
             amount_in,
             min_amount_out,
+            swap_to_params,
+            integrator_fee,
+            refund_in_near,
             &env::current_account_id(),
             0,
-            GAS_FOR_CALLBACK_SWAP_TO,
+            self.gas_config.callback_swap_to,
         ))
     }
 }
\ No newline at end of file
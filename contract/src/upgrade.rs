@@ -0,0 +1,211 @@
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// Layout of contract state before upgrade, i.e. of the version
+/// which has no upgrade methods yet
+#[derive(BorshDeserialize)]
+pub struct OldContract {
+    owner: AccountId,
+    manager: AccountId,
+    relayer: AccountId,
+    transfer_token: AccountId,
+    blockchain_router: AccountId,
+    num_of_this_blockchain: u64,
+    min_token_amount: u128,
+    max_token_amount: u128,
+    acc_token_fee: u128,
+    fee_amount_of_blockchain: u128,
+    rubic_addresses: LookupMap<u64, String>,
+    existing_other_blockchain: LookupSet<u64>,
+    blockchain_crypto_fee: LookupMap<u64, U128>,
+    processed_tx: LookupSet<String>,
+    is_running: bool,
+}
+
+/// Other blockchain added before upgrade
+/// * `blockchain_num` - uuid of blockchain
+/// * `blockchain_info` - blockchain metadata
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MigratedBlockchain {
+    pub blockchain_num: u64,
+    pub blockchain_info: BlockchainInfo,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// CONTRACT UPGRADE
+    /// Stores new contract code until it is deployed by `deploy_upgrade`.
+    /// Staged code can be checked with `get_staged_code_hash` view.
+    #[payable]
+    pub fn stage_upgrade(&mut self, code: Base64VecU8) {
+        self.assert_owner();
+        self.assert_no_upgrade_in_progress();
+
+        let code: Vec<u8> = code.into();
+        assert!(!code.is_empty(), "Code must not be empty");
+
+        self.staged_code.set(&code);
+    }
+
+    /// Removes staged code. If upgrade was started but `migrate` failed,
+    /// contract returns to the pause state it had before the upgrade.
+    #[payable]
+    pub fn cancel_upgrade(&mut self) {
+        self.assert_owner();
+
+        self.staged_code.remove();
+        if let Some(is_running) = self.running_before_upgrade.take() {
+            self.is_running = is_running;
+        }
+    }
+
+    /// Deploys staged code and calls `migrate` in one batched promise.
+    /// Contract is paused until `migrate` is executed.
+    /// * `code_hash` - hex encoded sha256 of staged code,
+    ///                 must match `get_staged_code_hash`
+    /// * `migrate_args` - JSON arguments of `migrate` of staged code.
+    ///                    If _None_ `migrate` is called without arguments
+    #[payable]
+    pub fn deploy_upgrade(&mut self, code_hash: String, migrate_args: Option<String>) -> Promise {
+        self.assert_owner();
+        self.assert_no_upgrade_in_progress();
+
+        let code = self.staged_code.get().expect("No staged code");
+        assert_eq!(
+            hex::encode(env::sha256(&code)),
+            code_hash,
+            "Wrong staged code hash",
+        );
+        // Deploy action and the rest of this call are covered by
+        // GAS_FOR_DEPLOY_UPGRADE, all other gas is attached to `migrate`
+        let migrate_gas = env::prepaid_gas()
+            .saturating_sub(env::used_gas() + GAS_FOR_DEPLOY_UPGRADE);
+        assert!(migrate_gas >= GAS_FOR_MIGRATE, "Not enough gas for upgrade");
+
+        self.running_before_upgrade = Some(self.is_running);
+        self.is_running = false;
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                b"migrate".to_vec(),
+                migrate_args.unwrap_or_else(|| "{}".to_string()).into_bytes(),
+                0,
+                migrate_gas,
+            )
+    }
+
+    /// Converts state of the previous version to the current layout.
+    /// Previous version has no `deploy_upgrade`, so code is deployed
+    /// and `migrate` is called by the key of contract account.
+    /// Available liquidity is set by balance of `transfer_token`
+    /// which is not accrued fee.
+    /// * `transfer_token_decimals` - decimals of `transfer_token`
+    /// * `other_blockchains` - all added other blockchains with metadata.
+    ///                         Their Rubic addresses are moved as well
+    #[init(ignore_state)]
+    pub fn migrate(
+        transfer_token_decimals: u8,
+        other_blockchains: Vec<MigratedBlockchain>,
+    ) -> Self {
+        assert_eq!(
+            env::predecessor_account_id(),
+            env::current_account_id(),
+            "Only for contract itself",
+        );
+
+        let mut old: OldContract = env::state_read()
+            .expect("Contract is not initialized");
+
+        let mut contract = Self {
+            owner: old.owner,
+            manager: old.manager,
+            relayer: old.relayer,
+            transfer_token: old.transfer_token,
+            blockchain_router: old.blockchain_router,
+            transfer_token_decimals,
+            num_of_this_blockchain: old.num_of_this_blockchain,
+            min_token_amount: old.min_token_amount,
+            max_token_amount: old.max_token_amount,
+            fee_amount_of_blockchain: old.fee_amount_of_blockchain,
+            acc_token_fee: old.acc_token_fee,
+            rubic_addresses: UnorderedMap::new(StorageKey::RubicAddresses),
+            other_blockchains: UnorderedMap::new(StorageKey::OtherBlockchains),
+            dexes: UnorderedMap::new(StorageKey::Dexes),
+            dex_referral_id: None,
+            referral_fee_share: 0,
+            referral_balances: LookupMap::new(StorageKey::ReferralBalances),
+            integrators: UnorderedMap::new(StorageKey::Integrators),
+            integrator_fees: LookupMap::new(StorageKey::IntegratorFees),
+            gas_config: GasConfig::default(),
+            escrows: LookupMap::new(StorageKey::Escrows),
+            next_escrow_id: 0,
+            escrow_timeout: escrow::DEFAULT_ESCROW_TIMEOUT,
+            liquidity: Liquidity {
+                available: 0,
+                reserved: 0,
+                fees: old.acc_token_fee,
+            },
+            is_swap_output_echo_enabled: false,
+            lp_token: FungibleToken::new(StorageKey::LpShares),
+            lp_liquidity: 0,
+            lp_fee_share: 0,
+            withdrawal_queue: Vector::new(StorageKey::WithdrawalQueue),
+            withdrawal_queue_head: 0,
+            queued_withdrawals: 0,
+            fee_beneficiaries: Vec::new(),
+            beneficiary_fees: LookupMap::new(StorageKey::BeneficiaryFees),
+            fee_distribution_history: Vector::new(StorageKey::FeeDistributionHistory),
+            fee_treasury: None,
+            fee_schedule: FeeSchedule::default(),
+            account_volumes: LookupMap::new(StorageKey::AccountVolumes),
+            holder_balance_source: None,
+            discount_tiers: Vec::new(),
+            relayer_compensations: LookupMap::new(StorageKey::RelayerCompensations),
+            gas_compensations_count: 0,
+            near_price: 0,
+            relayer_compensation_balance: 0,
+            pending_payouts: LookupMap::new(StorageKey::PendingPayouts),
+            blockchain_crypto_fee: old.blockchain_crypto_fee,
+            processed_tx: old.processed_tx,
+            is_running: old.is_running,
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            running_before_upgrade: None,
+        };
+
+        // Old collections are not iterable, so blockchains are re-keyed
+        // by the list given by owner
+        for blockchain in other_blockchains {
+            let blockchain_num = blockchain.blockchain_num;
+            assert!(
+                old.existing_other_blockchain.remove(&blockchain_num),
+                "The blockchain was not added"
+            );
+            assert!(
+                contract.other_blockchains
+                    .insert(&blockchain_num, &blockchain.blockchain_info)
+                    .is_none(),
+                "Blockchain already added"
+            );
+            if let Some(rubic_address) = old.rubic_addresses.remove(&blockchain_num) {
+                contract.rubic_addresses.insert(&blockchain_num, &rubic_address);
+            }
+        }
+        contract.validate_config();
+        contract.request_liquidity_sync();
+
+        contract
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_no_upgrade_in_progress(&self) {
+        assert!(
+            self.running_before_upgrade.is_none(),
+            "Upgrade in progress",
+        );
+    }
+}
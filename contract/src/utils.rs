@@ -8,18 +8,38 @@ impl Contract {
         swap_to: &SwapToParams, 
     ) {
         assert!(
-            swap_to.new_address.len() > 0,
+            !swap_to.new_address.is_empty(),
             "New address must not be empty"
         );
         assert!(
-            swap_to.second_path.len() > 0,
+            !is_deadline_passed(&swap_to.deadline),
+            "Swap deadline expired"
+        );
+        assert!(
+            !swap_to.second_path.is_empty(),
             "Second path must not be empty"
         );
         assert!(
-            self.existing_other_blockchain.contains(&swap_to.blockchain) &&
             swap_to.blockchain != self.num_of_this_blockchain,
             "Wrong choose of blockchain"
         );
+        let blockchain = self.other_blockchains
+            .get(&swap_to.blockchain)
+            .expect("Wrong choose of blockchain");
+        assert!(
+            blockchain.address_format.is_valid_address(&swap_to.new_address),
+            "Wrong format of new address"
+        );
+        assert!(
+            swap_to.second_path
+                .iter()
+                .all(|address| blockchain.address_format.is_valid_address(address)),
+            "Wrong format of address in second path"
+        );
+        assert!(
+            swap_to.min_amount_out.parse::<u128>().is_ok(),
+            "Min amount out must be an unsigned integer"
+        );
         assert!(
             swap_to.second_path[0] == 
             self.rubic_addresses.get(&swap_to.blockchain).unwrap(),
@@ -27,15 +47,21 @@ impl Contract {
         );
     }
 
+    pub fn check_amount_in(&self, amount_in: u128) -> Result<(), &'static str> {
+        if amount_in < self.min_token_amount {
+            return Err("Not enough tokens");
+        }
+        if amount_in > self.max_token_amount {
+            return Err("Too much tokens requested");
+        }
+
+        Ok(())
+    }
+
     pub fn validate_amount_in(&self, amount_in: &U128) {
-        assert!(
-            u128::from(*amount_in) >= self.min_token_amount,
-            "Not enough tokens",
-        );
-        assert!(
-            u128::from(*amount_in) <= self.max_token_amount,
-            "Too much tokens requested",
-        )
+        if let Err(err) = self.check_amount_in(u128::from(*amount_in)) {
+            env::panic(err.as_bytes());
+        }
     }
 
     pub fn validate_token_in_is_transfer(&self, token_in: &AccountId) {
@@ -54,41 +80,239 @@ impl Contract {
         );
     }
 
-    pub fn validate_swap_actions(&self, swap_actions: &Vec<SwapAction>) {
+    pub fn validate_swap_actions(
+        &self,
+        swap_actions: &[Action],
+        token_in: &AccountId,
+    ) {
         assert!(
-            swap_actions.len() > 0,
+            !swap_actions.is_empty(),
             "Firt path must not be empty"
         );
         let swaps_len = swap_actions.len();
-        let min_amount_out = 
-            swap_actions[swaps_len-1].min_amount_out;
-        let token_out = 
-            swap_actions[swaps_len-1].token_out.clone();
+        let min_amount_out = swap_actions[swaps_len-1]
+            .min_amount_out()
+            .expect("Last action in first path must have amount out");
         
+        self.validate_amount_in(&min_amount_out);
+        self.validate_swap_route(swap_actions, token_in, &self.transfer_token);
+    }
+
+    /// Checks that `actions` swap `token_in` for `token_out` hop by hop:
+    /// first action takes `token_in`, every action takes output of previous
+    /// one and last action gives `token_out`
+    pub fn check_swap_route(
+        &self,
+        actions: &[Action],
+        token_in: &AccountId,
+        token_out: &AccountId,
+    ) -> Result<(), &'static str> {
+        let (first_action, last_action) = match (actions.first(), actions.last()) {
+            (Some(first_action), Some(last_action)) => (first_action, last_action),
+            _ => return Err("Swap route must not be empty"),
+        };
+
+        if first_action.token_in() != token_in {
+            return Err("First token in swap route must be the input token");
+        }
+        if !actions
+            .windows(2)
+            .all(|hops| hops[0].token_out() == hops[1].token_in()) 
+        {
+            return Err("Token out of every hop must be token in of the next hop");
+        }
+        if last_action.token_out() != token_out {
+            return Err("Last token in swap route must be the output token");
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_swap_route(
+        &self,
+        actions: &[Action],
+        token_in: &AccountId,
+        token_out: &AccountId,
+    ) {
+        if let Err(err) = self.check_swap_route(actions, token_in, token_out) {
+            env::panic(err.as_bytes());
+        }
+    }
+
+    /// Checks actions of RefFinanceReceiverMessage for swaps from 
+    /// other blockchain to `token_out`. Output tokens must be returned 
+    /// to this contract by plain transfer, so `client_echo` is not allowed.
+    /// Min amount out of the last action must cover `min_amount_out`.
+    pub fn check_ref_finance_actions(
+        &self,
+        actions: &[Action],
+        client_echo: &Option<String>,
+        token_out: &AccountId,
+        min_amount_out: U128,
+    ) -> Result<(), &'static str> {
+        if actions.is_empty() {
+            return Err("Must be 1 or more SwapAction in msg");
+        }
+        if client_echo.is_some() {
+            return Err("Client echo is not supported for swaps from other blockchain");
+        }
+
+        self.check_swap_route(actions, &self.transfer_token, token_out)?;
+
+        let amount_out = actions
+            .last()
+            .unwrap()
+            .min_amount_out()
+            .ok_or("Last action in swap route must have amount out")?;
+        if u128::from(amount_out) < u128::from(min_amount_out) {
+            return Err("Min amount out of swap route does not cover min amount out");
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_config(&self) {
+        assert!(
+            self.min_token_amount <= self.max_token_amount,
+            "Min token amount must not exceed max token amount",
+        );
+        assert!(
+            self.fee_amount_of_blockchain < FEE_DENOMINATOR,
+            "Fee amount must be less than 1_000_000",
+        );
         assert!(
-            u128::from(min_amount_out) >= self.min_token_amount,
-            "Not enough tokens",
+            u128::from(self.referral_fee_share) <= BASIS_POINTS_DENOMINATOR,
+            "Referral fee share must not exceed 10000",
         );
         assert!(
-            u128::from(min_amount_out) <= self.max_token_amount,
-            "Too much tokens requested",
+            u128::from(self.lp_fee_share) <= BASIS_POINTS_DENOMINATOR,
+            "LP fee share must not exceed 10000",
         );
         assert!(
-            token_out == self.transfer_token,
-            "Last token in first path must be Rubic"
+            self.other_blockchains.get(&self.num_of_this_blockchain).is_none(),
+            "This blockchain is registered as other blockchain",
         );
+        assert!(
+            self.escrow_timeout >= escrow::MIN_ESCROW_TIMEOUT,
+            "Escrow timeout must be at least 1 hour",
+        );
+        self.validate_fee_schedule();
+        self.validate_gas_config();
+    }
+
+    fn validate_fee_schedule(&self) {
+        for tiers in [&self.fee_schedule.amount_tiers, &self.fee_schedule.volume_tiers].iter() {
+            assert!(
+                tiers.iter().all(|tier| u128::from(tier.fee_amount) < FEE_DENOMINATOR),
+                "Fee amount of tier must be less than 1_000_000",
+            );
+            assert!(
+                tiers
+                    .windows(2)
+                    .all(|pair| u128::from(pair[0].min_amount) < u128::from(pair[1].min_amount)),
+                "Fee tiers must be sorted by min amount",
+            );
+        }
     }
 
-    pub fn validate_swap_from(&self, swap_from: &SwapFromParams) {
+    pub fn validate_integrator_info(&self, integrator_info: &IntegratorInfo) {
         assert!(
-            u128::from(swap_from.amount_in_with_fee) >= 
-            self.min_token_amount,
-            "Not enough tokens",
+            env::is_valid_account_id(integrator_info.account_id.as_bytes()),
+            "Wrong integrator account"
         );
         assert!(
-            u128::from(swap_from.amount_in_with_fee) <= 
-            self.max_token_amount,
-            "Too much tokens requested",
+            u128::from(integrator_info.fee) <= MAX_INTEGRATOR_FEE,
+            "Integrator fee must not exceed 30000"
         );
     }
-} 
\ No newline at end of file
+
+    /// Checks `swap_from` params and message. Returns parsed message.
+    /// Does not panic, so that one bad swap does not revert a batch.
+    pub fn check_swap_from(
+        &self,
+        swap_from: &SwapFromParams,
+        msg: &Option<String>,
+    ) -> Result<Option<SwapFromMessage>, &'static str> {
+        if is_deadline_passed(&swap_from.deadline) {
+            return Err("Swap request expired");
+        }
+        if let Some(referrer) = &swap_from.referrer {
+            if referrer.as_ref() == swap_from.new_address.as_ref() {
+                return Err("Referrer must not be the receiver");
+            }
+        }
+        if swap_from.blockchain == self.num_of_this_blockchain ||
+            self.other_blockchains.get(&swap_from.blockchain).is_none()
+        {
+            return Err("Wrong choose of blockchain");
+        }
+        if let Some(integrator_id) = &swap_from.integrator {
+            if self.integrators.get(integrator_id).is_none() {
+                return Err("Unknown integrator");
+            }
+        }
+        if let Some(dex_id) = &swap_from.dex {
+            if self.dexes.get(dex_id).is_none() {
+                return Err("Unknown dex");
+            }
+        }
+        if self.processed_tx.contains(&swap_from.original_tx_hash) {
+            return Err("Swap already processed");
+        }
+
+        let amounts = self.calculate_swap_from(
+            swap_from.blockchain,
+            u128::from(swap_from.amount_in_with_fee),
+            swap_from.new_address.as_ref(),
+            &swap_from.integrator,
+            0,
+        )?;
+        self.check_amount_in(amounts.gross_amount)?;
+        if amounts.net_amount == 0 {
+            return Err("Amount does not cover fee and relayer compensation");
+        }
+        self.check_liquidity(amounts.gross_amount)?;
+
+        // User gets `transfer_token` if it is too late to swap
+        if is_deadline_passed(&swap_from.swap_deadline) {
+            return Ok(None);
+        }
+
+        let message = match msg {
+            Some(msg) => Some(
+                serde_json::from_str::<SwapFromMessage>(msg)
+                    .map_err(|_| "Wrong SwapFromMessage format")?
+            ),
+            None => None,
+        };
+        match &message {
+            Some(SwapFromMessage::SplitSwap { routes }) => {
+                self.check_swap_routes(
+                    routes,
+                    &self.transfer_token,
+                    swap_from.token_out.as_ref(),
+                    swap_from.amount_out_min,
+                )?;
+                split::split_amount(amounts.net_amount, routes)?;
+            }
+            Some(SwapFromMessage::RefFinance(RefFinanceReceiverMessage::ExecuteSwap {
+                actions, client_echo, ..
+            })) => {
+                self.check_ref_finance_actions(
+                    actions,
+                    client_echo,
+                    swap_from.token_out.as_ref(),
+                    swap_from.amount_out_min,
+                )?;
+            }
+            None => {}
+        }
+
+        Ok(message)
+    }
+}
+
+/// _true_ if `deadline` is set and current block is later
+pub(crate) fn is_deadline_passed(deadline: &Option<U64>) -> bool {
+    deadline.map_or(false, |deadline| env::block_timestamp() > u64::from(deadline))
+}
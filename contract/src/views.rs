@@ -6,6 +6,45 @@ impl Contract {
         1
     }
 
+    /// Whole configuration of contract in one call
+    pub fn get_config(&self) -> ContractConfig {
+        ContractConfig {
+            version: self.get_version(),
+            owner: self.owner.clone(),
+            manager: self.manager.clone(),
+            relayer: self.relayer.clone(),
+            transfer_token: self.transfer_token.clone(),
+            transfer_token_decimals: self.transfer_token_decimals,
+            blockchain_router: self.blockchain_router.clone(),
+            num_of_this_blockchain: self.num_of_this_blockchain,
+            min_token_amount: U128(self.min_token_amount),
+            max_token_amount: U128(self.max_token_amount),
+            fee_amount_of_blockchain: U128(self.fee_amount_of_blockchain),
+            dex_referral_id: self.dex_referral_id.clone(),
+            fee_treasury: self.fee_treasury.clone(),
+            referral_fee_share: self.referral_fee_share,
+            lp_fee_share: self.lp_fee_share,
+            gas_config: self.gas_config.clone(),
+            escrow_timeout: U64(self.escrow_timeout),
+            is_swap_output_echo_enabled: self.is_swap_output_echo_enabled,
+            is_running: self.is_running,
+            is_upgrade_in_progress: self.running_before_upgrade.is_some(),
+        }
+    }
+
+    pub fn get_escrow(&self, escrow_id: u64) -> Option<EscrowRecord> {
+        self.escrows.get(&escrow_id)
+    }
+
+    /// Time in nanoseconds after which user can refund not bridged tokens
+    pub fn get_escrow_timeout(&self) -> U64 {
+        U64(self.escrow_timeout)
+    }
+
+    pub fn get_gas_config(&self) -> GasConfig {
+        self.gas_config.clone()
+    }
+
     pub fn get_owner(&self) -> AccountId { 
         self.owner.clone()
     }
@@ -22,6 +61,10 @@ impl Contract {
         self.transfer_token.clone()
     }
 
+    pub fn get_transfer_token_decimals(&self) -> u8 {
+        self.transfer_token_decimals
+    }
+
     pub fn get_blockchain_router(&self) -> AccountId {
         self.blockchain_router.clone()
     }
@@ -38,6 +81,58 @@ impl Contract {
         U128(self.max_token_amount)
     }
 
+    pub fn get_liquidity(&self) -> LiquidityInfo {
+        self.to_liquidity_info()
+    }
+
+    pub fn get_fee_beneficiaries(&self) -> Vec<FeeBeneficiary> {
+        self.fee_beneficiaries.clone()
+    }
+
+    /// Fee in `transfer_token` accrued by beneficiary `account_id`
+    pub fn get_beneficiary_fee(&self, account_id: ValidAccountId) -> U128 {
+        U128(
+            self.beneficiary_fees
+                .get(account_id.as_ref())
+                .unwrap_or(0)
+        )
+    }
+
+    pub fn get_fee_distribution_history(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<FeeDistributionChange> {
+        let (from_index, to_index) = pagination_range(
+            self.fee_distribution_history.len(),
+            from_index,
+            limit,
+        );
+
+        (from_index..to_index)
+            .map(|index| self.fee_distribution_history.get(index).unwrap())
+            .collect()
+    }
+
+    pub fn get_lp_pool(&self) -> LpPoolInfo {
+        self.to_lp_pool_info()
+    }
+
+    /// Withdrawals waiting in queue, `from_index` is counted from
+    /// the first not processed request
+    pub fn get_withdrawal_queue(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<WithdrawalRequest> {
+        let queue_length = self.withdrawal_queue.len() - self.withdrawal_queue_head;
+        let (from_index, to_index) = pagination_range(queue_length, from_index, limit);
+
+        (from_index..to_index)
+            .map(|index| self.withdrawal_queue.get(self.withdrawal_queue_head + index).unwrap())
+            .collect()
+    }
+
     pub fn get_acc_token_fee(&self) -> U128 {
         U128(self.acc_token_fee)
     }
@@ -46,15 +141,241 @@ impl Contract {
         U128(self.fee_amount_of_blockchain)
     }
 
-    pub fn get_rubic_address(&self, blockchain_num: u64) -> String {
+    /// Amounts of swap from other `blockchain` to NEAR
+    /// * `amount_in_with_fee` - amount in decimals of transit token
+    ///                          in source blockchain
+    /// * `account_id` - receiver of tokens, its volume is used by fee tiers
+    /// * `integrator` - id of integrator taking its fee
+    /// * `holder_balance` - RBC balance of receiver for fee discount
+    pub fn quote_swap_from(
+        &self,
+        blockchain: u64,
+        amount_in_with_fee: U128,
+        account_id: Option<ValidAccountId>,
+        integrator: Option<String>,
+        holder_balance: Option<U128>,
+    ) -> SwapQuote {
+        let fee_discount = match holder_balance {
+            Some(balance) if self.holder_balance_source.is_some() => 
+                self.holder_fee_discount(u128::from(balance)),
+            _ => 0,
+        };
+
+        let amounts = self.calculate_swap_from(
+            blockchain,
+            u128::from(amount_in_with_fee),
+            &account_id.map(String::from).unwrap_or_default(),
+            &integrator,
+            fee_discount,
+        ).unwrap_or_else(|err| env::panic(err.as_bytes()));
+        let is_liquidity_enough = self.check_liquidity(amounts.gross_amount).is_ok();
+
+        let mut quote = self.to_swap_quote(amounts);
+        quote.is_amount_valid &= is_liquidity_enough;
+
+        quote
+    }
+
+    /// Compensation of relayer gas for swaps from `blockchain_num`
+    pub fn get_relayer_compensation(&self, blockchain_num: u64) -> Option<RelayerCompensation> {
+        self.relayer_compensations.get(&blockchain_num)
+    }
+
+    /// Amount of `transfer_token` per 1 NEAR
+    pub fn get_near_price(&self) -> U128 {
+        U128(self.near_price)
+    }
+
+    pub fn get_relayer_compensation_balance(&self) -> U128 {
+        U128(self.relayer_compensation_balance)
+    }
+
+    pub fn get_holder_balance_source(&self) -> Option<HolderBalanceSource> {
+        self.holder_balance_source.clone()
+    }
+
+    pub fn get_discount_tiers(&self) -> Vec<DiscountTier> {
+        self.discount_tiers.clone()
+    }
+
+    /// Amounts of swap from NEAR to other `blockchain`
+    /// * `transit_amount` - amount of `transfer_token`, i.e. `amount` of
+    ///                      transfer token or `min_amount_out` of last
+    ///                      swap action
+    /// * `account_id` - sender of tokens, its volume is used by fee tiers
+    /// * `integrator` - id of integrator taking its fee from `amount` of
+    ///                  transfer token. Fee of swaps by DEX is taken 
+    ///                  in token in before swap, so it is not quoted here
+    /// * `holder_balance` - RBC balance of sender for fee discount
+    pub fn quote_swap_to(
+        &self,
+        blockchain: u64,
+        transit_amount: U128,
+        account_id: Option<ValidAccountId>,
+        integrator: Option<String>,
+        holder_balance: Option<U128>,
+    ) -> SwapQuote {
+        let fee_discount = match holder_balance {
+            Some(balance) if self.holder_balance_source.is_some() => 
+                self.holder_fee_discount(u128::from(balance)),
+            _ => 0,
+        };
+        let integrator_fee = self.calculate_integrator_fee(
+            &integrator,
+            u128::from(transit_amount),
+        );
+
+        self.to_swap_quote(
+            self.calculate_transfer_to(
+                blockchain,
+                u128::from(transit_amount),
+                &account_id.map(String::from).unwrap_or_default(),
+                &integrator_fee,
+                fee_discount,
+            )
+        )
+    }
+
+    pub fn get_fee_schedule(&self) -> FeeSchedule {
+        self.fee_schedule.clone()
+    }
+
+    /// Volume of `account_id` in `transfer_token` during last 30 days,
+    /// tracked only while volume fee tiers are set
+    pub fn get_account_volume(&self, account_id: ValidAccountId) -> U128 {
+        U128(self.account_volume(account_id.as_ref()))
+    }
+
+    pub fn get_dex_referral_id(&self) -> Option<AccountId> {
+        self.dex_referral_id.clone()
+    }
+
+    /// Share of fee credited to referrer, in basis points
+    pub fn get_referral_fee_share(&self) -> u16 {
+        self.referral_fee_share
+    }
+
+    pub fn get_referral_balance(&self, referrer_id: ValidAccountId) -> U128 {
+        U128(
+            self.referral_balances
+                .get(referrer_id.as_ref())
+                .unwrap_or(0)
+        )
+    }
+
+    pub fn get_rubic_address(&self, blockchain_num: u64) -> Option<String> {
         self.rubic_addresses
             .get(&blockchain_num)
-            .expect("Wrong blockchain number")
+    }
+
+    /// Rubic addresses of all blockchains, including not registered ones
+    pub fn get_rubic_addresses(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(u64, String)> {
+        let keys = self.rubic_addresses.keys_as_vector();
+        let values = self.rubic_addresses.values_as_vector();
+        let (from_index, to_index) = pagination_range(keys.len(), from_index, limit);
+
+        (from_index..to_index)
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .collect()
     }
 
     pub fn existing_other_blockchain(&self, blockchain_num: u64) -> bool {
-        self.existing_other_blockchain
-            .contains(&blockchain_num)
+        self.other_blockchains
+            .get(&blockchain_num)
+            .is_some()
+    }
+
+    pub fn get_other_blockchain(&self, blockchain_num: u64) -> Option<BlockchainConfig> {
+        self.other_blockchains
+            .get(&blockchain_num)
+            .map(|info| self.to_blockchain_config(blockchain_num, info))
+    }
+
+    pub fn get_other_blockchains_count(&self) -> u64 {
+        self.other_blockchains.len()
+    }
+
+    pub fn get_other_blockchains(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<BlockchainConfig> {
+        let keys = self.other_blockchains.keys_as_vector();
+        let values = self.other_blockchains.values_as_vector();
+        let (from_index, to_index) = pagination_range(keys.len(), from_index, limit);
+
+        (from_index..to_index)
+            .map(|index| self.to_blockchain_config(
+                keys.get(index).unwrap(),
+                values.get(index).unwrap(),
+            ))
+            .collect()
+    }
+
+    pub fn get_integrator(&self, integrator_id: String) -> Option<IntegratorInfo> {
+        self.integrators.get(&integrator_id)
+    }
+
+    pub fn get_integrators(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(String, IntegratorInfo)> {
+        let keys = self.integrators.keys_as_vector();
+        let values = self.integrators.values_as_vector();
+        let (from_index, to_index) = pagination_range(keys.len(), from_index, limit);
+
+        (from_index..to_index)
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .collect()
+    }
+
+    /// Fee of `token_id` accrued by integrator `account_id`
+    pub fn get_integrator_fee(
+        &self,
+        account_id: ValidAccountId,
+        token_id: ValidAccountId,
+    ) -> U128 {
+        U128(
+            self.integrator_fees
+                .get(&(account_id.into(), token_id.into()))
+                .unwrap_or(0)
+        )
+    }
+
+    /// Payout of `token_id` which can be claimed by `account_id`
+    pub fn get_pending_payout(
+        &self,
+        account_id: ValidAccountId,
+        token_id: ValidAccountId,
+    ) -> U128 {
+        U128(
+            self.pending_payouts
+                .get(&(account_id.into(), token_id.into()))
+                .unwrap_or(0)
+        )
+    }
+
+    pub fn get_dex(&self, dex_id: String) -> Option<DexInfo> {
+        self.dexes.get(&dex_id)
+    }
+
+    pub fn get_dexes(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(String, DexInfo)> {
+        let keys = self.dexes.keys_as_vector();
+        let values = self.dexes.values_as_vector();
+        let (from_index, to_index) = pagination_range(keys.len(), from_index, limit);
+
+        (from_index..to_index)
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .collect()
     }
 
     pub fn is_processed_tx(&self, original_tx_hash: String) -> bool {
@@ -64,4 +385,65 @@ impl Contract {
     pub fn is_running(&self) -> bool {
         self.is_running 
     }
+
+    /// Hex encoded sha256 of code staged by `stage_upgrade`
+    pub fn get_staged_code_hash(&self) -> Option<String> {
+        self.staged_code
+            .get()
+            .map(|code| hex::encode(env::sha256(&code)))
+    }
+
+    pub fn is_upgrade_in_progress(&self) -> bool {
+        self.running_before_upgrade.is_some()
+    }
+}
+
+impl Contract {
+    fn to_blockchain_config(
+        &self,
+        blockchain_num: u64,
+        info: BlockchainInfo,
+    ) -> BlockchainConfig {
+        BlockchainConfig {
+            blockchain_num,
+            info,
+            rubic_address: self.rubic_addresses.get(&blockchain_num),
+        }
+    }
+}
+
+/// Returns `[from_index, to_index)` range of collection with `len` elements
+fn pagination_range(len: u64, from_index: Option<u64>, limit: Option<u64>) -> (u64, u64) {
+    let from_index = std::cmp::min(from_index.unwrap_or(0), len);
+    let to_index = std::cmp::min(
+        from_index.saturating_add(limit.unwrap_or(len)),
+        len,
+    );
+
+    (from_index, to_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_collection_by_default() {
+        assert_eq!(pagination_range(5, None, None), (0, 5));
+        assert_eq!(pagination_range(0, None, None), (0, 0));
+    }
+
+    #[test]
+    fn page_inside_collection() {
+        assert_eq!(pagination_range(10, Some(2), Some(3)), (2, 5));
+        assert_eq!(pagination_range(10, Some(8), Some(5)), (8, 10));
+        assert_eq!(pagination_range(10, Some(4), None), (4, 10));
+    }
+
+    #[test]
+    fn page_out_of_collection() {
+        assert_eq!(pagination_range(10, Some(15), Some(3)), (10, 10));
+        assert_eq!(pagination_range(10, Some(3), Some(0)), (3, 3));
+        assert_eq!(pagination_range(10, Some(3), Some(u64::MAX)), (3, 10));
+    }
 }
\ No newline at end of file
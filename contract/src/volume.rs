@@ -0,0 +1,56 @@
+use super::*;
+
+pub const DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+/// Length of rolling window of account volume, in days
+pub const VOLUME_WINDOW_DAYS: u64 = 30;
+
+/// Volume of account in `transfer_token` swapped during one day
+/// * `day` - number of day since unix epoch
+/// * `amount` - swapped amount
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VolumeBucket {
+    pub day: u64,
+    pub amount: u128,
+}
+
+impl Contract {
+    /// Volume of `account_id` in `transfer_token` during last 30 days
+    pub(crate) fn account_volume(&self, account_id: &AccountId) -> u128 {
+        let first_day = first_day_of_window();
+
+        self.account_volumes
+            .get(account_id)
+            .unwrap_or_default()
+            .iter()
+            .filter(|bucket| bucket.day >= first_day)
+            .map(|bucket| bucket.amount)
+            .sum()
+    }
+
+    /// Adds `amount` to rolling volume of `account_id`. 
+    /// Volume is tracked only if volume fee tiers are set.
+    pub(crate) fn record_volume(&mut self, account_id: &AccountId, amount: u128) {
+        if self.fee_schedule.volume_tiers.is_empty() {
+            return;
+        }
+
+        let today = env::block_timestamp() / DAY;
+        let first_day = first_day_of_window();
+        let mut buckets: Vec<VolumeBucket> = self.account_volumes
+            .get(account_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|bucket| bucket.day >= first_day)
+            .collect();
+
+        match buckets.last_mut() {
+            Some(bucket) if bucket.day == today => bucket.amount += amount,
+            _ => buckets.push(VolumeBucket { day: today, amount }),
+        }
+        self.account_volumes.insert(account_id, &buckets);
+    }
+}
+
+fn first_day_of_window() -> u64 {
+    (env::block_timestamp() / DAY).saturating_sub(VOLUME_WINDOW_DAYS - 1)
+}
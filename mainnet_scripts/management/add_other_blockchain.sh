@@ -1,5 +1,11 @@
 near call multichain.rubic-finance.near add_other_blockchain \
 	'{
-	"blockchain_num": 8
+	"blockchain_num": 8,
+	"blockchain_info": {
+		"name": "Solana",
+		"address_format": "Solana",
+		"chain_id": null,
+		"transit_token_decimals": 6
+	}
 	}' \
 		--accountId rubic-finance.near
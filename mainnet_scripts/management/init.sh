@@ -5,6 +5,7 @@ near call multichain.rubic-finance.near new \
 	"relayer_id": "rubic-finance.near",
 	"transfer_token": "dac17f958d2ee523a2206206994597c13d831ec7.factory.bridge.near",
 	"blockchain_router": "v2.ref-finance.near",
+	"transfer_token_decimals": 6,
 	"num_of_this_blockchain": 10,
 	"min_token_amount": "0",
 	"max_token_amount": "9999999999999999999999",
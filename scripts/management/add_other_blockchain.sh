@@ -1,5 +1,11 @@
 near call dev-1643193012129-24813219060028 add_other_blockchain \
 	'{
-	"blockchain_num": 1
+	"blockchain_num": 1,
+	"blockchain_info": {
+		"name": "Test blockchain",
+		"address_format": "Other",
+		"chain_id": null,
+		"transit_token_decimals": 18
+	}
 	}' \
 		--accountId maxik.testnet
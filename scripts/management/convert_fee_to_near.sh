@@ -0,0 +1,13 @@
+near call dev-1643193012129-24813219060028 convert_fee_to_near \
+	'{
+	"amount": "1000",
+	"actions": [ {
+		"pool_id": 34,
+		"token_in": "nusdt.ft-fin.testnet",
+		"token_out": "wrap.testnet",
+		"amount_out": "1000000000000000000000"
+	} ]
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1 \
+		--gas 200000000000000
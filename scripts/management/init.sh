@@ -5,6 +5,7 @@ near call dev-1643193012129-24813219060028 new \
 	"relayer_id": "maxik.testnet",
 	"transfer_token": "banana.ft-fin.testnet",
 	"blockchain_router": "ref-finance.testnet",
+	"transfer_token_decimals": 18,
 	"num_of_this_blockchain": 9,
 	"min_token_amount": "0",
 	"max_token_amount": "9999999999999999999999",
@@ -0,0 +1,15 @@
+near deploy --accountId dev-1643193012129-24813219060028 --wasmFile contract/res/crosschain_token_swap.wasm --initFunction migrate --initArgs \
+	'{
+	"transfer_token_decimals": 18,
+	"other_blockchains": [
+		{
+		"blockchain_num": 1,
+		"blockchain_info": {
+			"name": "Test blockchain",
+			"address_format": "Other",
+			"chain_id": null,
+			"transit_token_decimals": 18
+		}
+		}
+	]
+	}'
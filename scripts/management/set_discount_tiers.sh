@@ -0,0 +1,18 @@
+near call dev-1643193012129-24813219060028 set_holder_balance_source \
+	'{
+	"source": {
+		"contract_id": "rbc.ft-fin.testnet",
+		"method_name": "ft_balance_of"
+	}
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1
+near call dev-1643193012129-24813219060028 set_discount_tiers \
+	'{
+	"discount_tiers": [
+		{ "min_balance": "1000000000000000000000", "discount": 1000 },
+		{ "min_balance": "10000000000000000000000", "discount": 2500 }
+	]
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1
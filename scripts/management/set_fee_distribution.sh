@@ -0,0 +1,10 @@
+near call dev-1643193012129-24813219060028 set_fee_distribution \
+	'{
+	"beneficiaries": [
+		{ "account_id": "treasury.testnet", "share": 7000 },
+		{ "account_id": "relayer-ops.testnet", "share": 2000 },
+		{ "account_id": "buyback.testnet", "share": 1000 }
+	]
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1
@@ -0,0 +1,15 @@
+near call dev-1643193012129-24813219060028 set_near_price \
+	'{
+	"near_price": "5000000"
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1
+near call dev-1643193012129-24813219060028 set_relayer_compensation \
+	'{
+	"blockchain_num": 1,
+	"compensation": {
+		"Gas": { "near_amount": "30000000000000000000000" }
+	}
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1
@@ -0,0 +1,5 @@
+near call dev-1643193012129-24813219060028 set_swap_output_echo \
+	'{
+	"is_enabled": true
+	}' \
+		--accountId maxik.testnet
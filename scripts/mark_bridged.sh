@@ -0,0 +1,6 @@
+near call dev-1643193012129-24813219060028 mark_bridged \
+	'{
+	"escrow_id": 0
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1
@@ -0,0 +1,16 @@
+near call dev-1643193012129-24813219060028 storage_deposit \
+	'{
+	"account_id": "maxik.testnet",
+	"registration_only": true
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 12500000000000000000000
+near call nusdt.ft-fin.testnet ft_transfer_call \
+        '{
+        "receiver_id": "dev-1643193012129-24813219060028",
+        "amount": "1000",
+	"msg": "\"ProvideLiquidity\""
+        }' \
+                --accountId maxik.testnet \
+                --depositYocto 1 \
+                --gas 100000000000000
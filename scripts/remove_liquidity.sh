@@ -0,0 +1,7 @@
+near call dev-1643193012129-24813219060028 remove_liquidity \
+	'{
+	"shares": "1000"
+	}' \
+		--accountId maxik.testnet \
+		--depositYocto 1 \
+		--gas 100000000000000
@@ -0,0 +1,27 @@
+near call dev-1643193012129-24813219060028 swap_near_to_other_blockchain \
+        '{
+        "swap_actions": [
+                {
+                "pool_id": 17,
+                "token_in": "wrap.testnet",
+                "amount_in": "1000000000000000000000000",
+                "token_out": "nusdt.ft-fin.testnet",
+                "min_amount_out": "90"
+                }
+        ],
+        "swap_to_params": {
+                "second_path": [
+                        "first_token",
+                        "second_token",
+                        "third_token"
+                ],
+                "min_amount_out": "124124512542151125125",
+                "blockchain": 1,
+                "new_address": "new_address_string",
+                "swap_to_crypto": false,
+                "signature": "signature_string"
+        }
+        }' \
+                --accountId maxik.testnet \
+                --deposit 1 \
+                --gas 300000000000000